@@ -170,13 +170,13 @@ fn main() -> ! {
                         port,
                         mode,
                         &mut delay,
-                        &mut |tcp_client| {
+                        &mut |tcp_client, delay| {
                             defmt::info!("TCP connection to {:?}:{:?} successful", hostname, port);
                             defmt::info!("Hostname: {:?}", tcp_client.server_hostname());
                             defmt::info!("Sending HTTP Document: {:?}", http_document.as_str());
-                            match tcp_client.send_data(&http_document) {
-                                Ok(response) => {
-                                    defmt::info!("Response: {:?}", response)
+                            match tcp_client.send_data(http_document.as_bytes(), delay) {
+                                Ok(length) => {
+                                    defmt::info!("Sent {:?} bytes", length)
                                 }
                                 Err(e) => {
                                     defmt::error!("Response error: {:?}", e)
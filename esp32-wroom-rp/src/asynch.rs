@@ -0,0 +1,67 @@
+//! Async front end for executors such as Embassy, enabled by the `async` feature.
+//!
+//! Instead of spinning on the NINA firmware's ready/ack lines, the async protocol handler
+//! awaits them through [`embedded_hal_async::digital::Wait`] and talks to the ESP32 target
+//! over an [`embedded_hal_async::spi::SpiDevice`]. Commands are encoded and responses parsed
+//! exactly like the blocking [`Wifi`](crate::wifi::Wifi) does.
+//!
+//! The chip select line stays with the control pins, so the `SpiDevice` must not drive one
+//! of its own, e.g. build it with a dummy chip select pin.
+//!
+//! Waiting on the ready/ack lines isn't bounded by a timeout the way the blocking driver's
+//! is, since the async driver has no time source of its own. If the ESP32 target may stop
+//! responding, wrap calls in your executor's timeout, e.g. Embassy's
+//! `embassy_time::with_timeout`.
+//!
+//! Every call can be cancelled by dropping its future, which deselects the ESP32 target if
+//! a command or response was in flight. The target is then left with a partial command or
+//! an unread response though, so reset it with [`Wifi::init`](wifi::Wifi::init) before
+//! sending it anything else.
+//!
+//! ## Usage
+//!
+//! ```no_run
+//! use esp32_wroom_rp::asynch::{tcp_client::TcpClient, wifi::Wifi};
+//!
+//! let mut wifi = Wifi::init(spi_device, esp_pins, &mut delay).await?;
+//! wifi.join(SSID, PASSPHRASE).await?;
+//!
+//! while wifi.get_connection_status().await? != ConnectionStatus::Connected {
+//!     Timer::after_millis(100).await;
+//! }
+//!
+//! let ip_address = wifi.resolve("example.com").await?;
+//! let mut connection = TcpClient::build(&mut wifi)
+//!     .open(ip_address, 80, TransportMode::Tcp, &mut delay)
+//!     .await?;
+//!
+//! connection.send_all(http_document.as_bytes(), &mut delay).await?;
+//!
+//! let mut response = [0u8; 512];
+//! let length = connection.receive(&mut response).await?;
+//! defmt::info!("Response: {:?}", &response[..length]);
+//!
+//! connection.close().await?;
+//! ```
+//!
+
+pub mod gpio;
+pub mod tcp_client;
+pub mod wifi;
+
+mod spi;
+
+use crate::protocol::ClientCredentials;
+
+// Async counterpart of the blocking NinaProtocolHandler, sending the same commands over an
+// async SPI device and awaiting the ready/ack lines.
+#[derive(Debug)]
+pub(crate) struct NinaProtocolHandler<S, C> {
+    /// An async SpiDevice instance
+    pub bus: S,
+    /// An async EspControlInterface instance
+    pub control_pins: C,
+    /// Never set, as the async front end can't store a client certificate and private key
+    /// for mutual TLS on the NINA firmware yet
+    pub client_credentials: ClientCredentials,
+}
@@ -0,0 +1,104 @@
+//! Async control of the GPIO pins of a connected ESP32-WROOM target WiFi board.
+//!
+//! ## Usage
+//!
+//! The same [`EspControlPins`] as for the blocking driver are used, only built from pins that
+//! implement the embedded-hal 1.0 digital traits, with an ACK pin that also implements
+//! [`Wait`]:
+//!
+//! ```no_run
+//! let esp_pins = esp32_wroom_rp::gpio::EspControlPins {
+//!     cs: Output::new(p.PIN_7, Level::High),
+//!     gpio0: Output::new(p.PIN_2, Level::High),
+//!     resetn: Output::new(p.PIN_11, Level::High),
+//!     ack: Input::new(p.PIN_10, Pull::None),
+//! };
+//! ```
+
+use embedded_hal_1::digital::{InputPin, OutputPin};
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+
+use crate::gpio::EspControlPins;
+use crate::{BusError, Error};
+
+/// Provides an internal pin interface that abstracts the extra control lines that
+/// are separate from a data bus (e.g. SPI/I2C), awaiting the ready/ack lines instead
+/// of spinning on them.
+///
+/// Not meant to be used outside of the crate.
+#[allow(async_fn_in_trait)]
+pub trait EspControlInterface {
+    /// Initializes all controls pins to set ready communication with the NINA firmware.
+    fn init(&mut self) -> Result<(), Error>;
+
+    /// Resets communication with the NINA firmware.
+    async fn reset<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error>;
+
+    /// Tells the NINA firmware we're about to send it a protocol command.
+    fn esp_select(&mut self) -> Result<(), Error>;
+
+    /// Tells the NINA firmware we're done sending it a protocol command.
+    fn esp_deselect(&mut self) -> Result<(), Error>;
+
+    /// Waits for the NINA firmware to be ready to send it a protocol command. Unlike the
+    /// blocking driver, there's no timeout: wrap calls in one from your executor instead.
+    async fn wait_for_esp_ready(&mut self) -> Result<(), Error>;
+
+    /// Waits for the NINA firmware to acknowledge it's ready to receive more commands. Like
+    /// [`EspControlInterface::wait_for_esp_ready`], it waits for as long as it takes.
+    async fn wait_for_esp_ack(&mut self) -> Result<(), Error>;
+}
+
+impl<CS, GPIO0, RESETN, ACK> EspControlInterface for EspControlPins<CS, GPIO0, RESETN, ACK>
+where
+    CS: OutputPin,
+    GPIO0: OutputPin,
+    RESETN: OutputPin,
+    ACK: InputPin + Wait,
+{
+    fn init(&mut self) -> Result<(), Error> {
+        // Chip select is active-low, so we'll initialize it to a driven-high state
+        self.cs.set_high().map_err(BusError::control_pin)?;
+        self.gpio0.set_high().map_err(BusError::control_pin)?;
+        self.resetn.set_high().map_err(BusError::control_pin)?;
+        self.ack.is_low().map_err(BusError::control_pin)?;
+        Ok(())
+    }
+
+    async fn reset<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error> {
+        self.gpio0.set_high().map_err(BusError::control_pin)?;
+        self.cs.set_high().map_err(BusError::control_pin)?;
+        self.resetn.set_low().map_err(BusError::control_pin)?;
+        delay.delay_ms(10).await;
+        self.resetn.set_high().map_err(BusError::control_pin)?;
+        delay.delay_ms(750).await;
+        Ok(())
+    }
+
+    fn esp_select(&mut self) -> Result<(), Error> {
+        self.cs.set_low().map_err(BusError::control_pin)?;
+        Ok(())
+    }
+
+    fn esp_deselect(&mut self) -> Result<(), Error> {
+        self.cs.set_high().map_err(BusError::control_pin)?;
+        Ok(())
+    }
+
+    async fn wait_for_esp_ready(&mut self) -> Result<(), Error> {
+        self.ack
+            .wait_for_low()
+            .await
+            .map_err(BusError::control_pin)?;
+        Ok(())
+    }
+
+    async fn wait_for_esp_ack(&mut self) -> Result<(), Error> {
+        self.ack
+            .wait_for_high()
+            .await
+            .map_err(BusError::control_pin)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+//! Serial Peripheral Interface (SPI) for the async front end, sending the same commands
+//! as the blocking one.
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::spi::SpiDevice;
+
+use super::gpio::EspControlInterface;
+use super::NinaProtocolHandler;
+use crate::network::{ConnectionState, IpAddress, NetworkError, Port, Socket, TransportMode};
+use crate::protocol::operation::Operation;
+use crate::protocol::request::{self, Parse, Request};
+use crate::protocol::{ControlByte, NinaCommand, NinaParam, NinaResponse, NinaResponseReader};
+use crate::spi::{DATA_SENT_CHECK_INTERVAL_MS, MAX_DATA_SENT_CHECKS};
+use crate::tcp_client::ReceiveData;
+use crate::wifi::ConnectionStatus;
+use crate::{BusError, Error, FirmwareVersion};
+
+// The number of command bytes handed to the SPI device in a single write
+const WRITE_CHUNK_LENGTH: usize = 32;
+
+impl<S, C> NinaProtocolHandler<S, C>
+where
+    S: SpiDevice,
+    C: EspControlInterface,
+{
+    pub(crate) fn init(&mut self) -> Result<(), Error> {
+        self.control_pins.init()
+    }
+
+    pub(crate) async fn reset<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error> {
+        self.control_pins.reset(delay).await
+    }
+
+    pub(crate) async fn get_fw_version(&mut self) -> Result<FirmwareVersion, Error> {
+        self.request(request::get_fw_version()).await
+    }
+
+    pub(crate) async fn set_passphrase(
+        &mut self,
+        ssid: &str,
+        passphrase: &str,
+    ) -> Result<(), Error> {
+        self.request(request::set_passphrase(ssid, passphrase)?)
+            .await
+    }
+
+    pub(crate) async fn get_conn_status(&mut self) -> Result<ConnectionStatus, Error> {
+        self.request(request::get_conn_status()).await
+    }
+
+    pub(crate) async fn disconnect(&mut self) -> Result<(), Error> {
+        self.request(request::disconnect()).await
+    }
+
+    pub(crate) async fn resolve(&mut self, hostname: &str) -> Result<IpAddress, Error> {
+        self.request(request::req_host_by_name(hostname)?).await?;
+
+        self.request(request::get_host_by_name()).await
+    }
+
+    pub(crate) async fn get_socket(&mut self) -> Result<Socket, Error> {
+        self.request(request::get_socket()).await
+    }
+
+    pub(crate) async fn start_client_tcp(
+        &mut self,
+        socket: Socket,
+        ip: IpAddress,
+        port: Port,
+        mode: &TransportMode,
+    ) -> Result<(), Error> {
+        self.client_credentials.check(mode)?;
+
+        self.request(request::start_client_tcp(socket, ip, port, mode)?)
+            .await
+    }
+
+    pub(crate) async fn start_client_tcp_hostname(
+        &mut self,
+        socket: Socket,
+        hostname: &str,
+        port: Port,
+        mode: &TransportMode,
+    ) -> Result<(), Error> {
+        self.client_credentials.check(mode)?;
+
+        self.request(request::start_client_tcp_hostname(
+            socket, hostname, port, mode,
+        )?)
+        .await
+    }
+
+    pub(crate) async fn stop_client_tcp(&mut self, socket: Socket) -> Result<(), Error> {
+        self.request(request::stop_client_tcp(socket)?).await
+    }
+
+    pub(crate) async fn get_client_state_tcp(
+        &mut self,
+        socket: Socket,
+    ) -> Result<ConnectionState, Error> {
+        self.request(request::get_client_state_tcp(socket)?).await
+    }
+
+    pub(crate) async fn send_data<D: DelayNs>(
+        &mut self,
+        data: &[u8],
+        socket: Socket,
+        delay: &mut D,
+    ) -> Result<usize, Error> {
+        let written = self.request(request::send_data_tcp(data, &socket)?).await?;
+        if written == 0 {
+            return Ok(0);
+        }
+
+        // Only count the bytes as sent once NINA firmware acknowledges them
+        for check in 0..MAX_DATA_SENT_CHECKS {
+            if check > 0 {
+                delay.delay_ms(DATA_SENT_CHECK_INTERVAL_MS.into()).await;
+            }
+            if self.request(request::data_sent_tcp(socket)?).await? {
+                return Ok(written);
+            }
+        }
+
+        Err(NetworkError::SendFailed.into())
+    }
+
+    pub(crate) async fn avail_data_tcp(&mut self, socket: Socket) -> Result<usize, Error> {
+        self.request(request::avail_data_tcp(socket)?).await
+    }
+
+    pub(crate) async fn get_databuf_tcp(
+        &mut self,
+        socket: Socket,
+        data: &mut [u8],
+    ) -> Result<usize, Error> {
+        self.request(request::get_databuf_tcp(socket, data)?).await
+    }
+
+    pub(crate) async fn receive_data(
+        &mut self,
+        socket: Socket,
+        data: &mut [u8],
+    ) -> Result<usize, Error> {
+        let mut receive_data = ReceiveData::new(data);
+
+        while !receive_data.is_done() {
+            let available = self.avail_data_tcp(socket).await?;
+            let bytes_read = match receive_data.next_buffer(available) {
+                Some(buffer) => self.get_databuf_tcp(socket, buffer).await?,
+                None => 0,
+            };
+            receive_data.received(bytes_read);
+        }
+
+        Ok(receive_data.bytes_read())
+    }
+
+    // Sends a request shared with the blocking protocol handler and interprets the response
+    async fn request<P: NinaParam, T>(
+        &mut self,
+        request: Request<P, impl Parse<T>>,
+    ) -> Result<T, Error> {
+        self.execute(&request.operation).await?;
+
+        let result = self.receive(&request.operation, request.num_params).await?;
+
+        (request.parse)(&result)
+    }
+
+    async fn execute<P: NinaParam>(&mut self, operation: &Operation<P>) -> Result<(), Error> {
+        let selected = Selected::wait_for(&mut self.control_pins).await?;
+
+        let result = send_operation(&mut self.bus, operation).await;
+
+        // Always release the ESP32 target, even when sending the command failed
+        selected.release()?;
+
+        result
+    }
+
+    async fn receive<P: NinaParam>(
+        &mut self,
+        operation: &Operation<P>,
+        expected_num_params: Option<u8>,
+    ) -> Result<NinaResponse, Error> {
+        let selected = Selected::wait_for(&mut self.control_pins).await?;
+
+        let result = read_response(&mut self.bus, &operation.command, expected_num_params).await;
+
+        // Always release the ESP32 target, even when the response was invalid
+        selected.release()?;
+
+        result
+    }
+}
+
+// Keeps the ESP32 target selected while a command or response is on the bus. If the future
+// doing so is dropped partway through, the target is released on drop rather than left
+// selected with half a frame sent.
+struct Selected<'c, C: EspControlInterface> {
+    control_pins: Option<&'c mut C>,
+}
+
+impl<'c, C: EspControlInterface> Selected<'c, C> {
+    async fn wait_for(control_pins: &'c mut C) -> Result<Self, Error> {
+        control_pins.wait_for_esp_ready().await?;
+        control_pins.esp_select()?;
+
+        let mut selected = Selected {
+            control_pins: Some(control_pins),
+        };
+        if let Some(control_pins) = selected.control_pins.as_mut() {
+            control_pins.wait_for_esp_ack().await?;
+        }
+
+        Ok(selected)
+    }
+
+    fn release(mut self) -> Result<(), Error> {
+        match self.control_pins.take() {
+            Some(control_pins) => control_pins.esp_deselect(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<C: EspControlInterface> Drop for Selected<'_, C> {
+    fn drop(&mut self) {
+        if let Some(control_pins) = self.control_pins.take() {
+            // Nothing is left to report the error to when a future was dropped
+            control_pins.esp_deselect().ok();
+        }
+    }
+}
+
+// Writes the encoded command in chunks, so that large payloads are never copied whole
+async fn send_operation<S: SpiDevice, P: NinaParam>(
+    bus: &mut S,
+    operation: &Operation<P>,
+) -> Result<(), Error> {
+    let mut chunk = [0u8; WRITE_CHUNK_LENGTH];
+    let mut length: usize = 0;
+
+    for byte in operation.bytes() {
+        chunk[length] = byte;
+        length += 1;
+
+        if length == WRITE_CHUNK_LENGTH {
+            bus.write(&chunk).await.map_err(BusError::transfer)?;
+            length = 0;
+        }
+    }
+
+    if length > 0 {
+        bus.write(&chunk[..length])
+            .await
+            .map_err(BusError::transfer)?;
+    }
+
+    Ok(())
+}
+
+async fn read_response<S: SpiDevice>(
+    bus: &mut S,
+    cmd: &NinaCommand,
+    expected_num_params: Option<u8>,
+) -> Result<NinaResponse, Error> {
+    let mut reader = NinaResponseReader::new(cmd, expected_num_params);
+    while !reader.push(get_byte(bus).await?)? {}
+
+    Ok(reader.finish())
+}
+
+async fn get_byte<S: SpiDevice>(bus: &mut S) -> Result<u8, Error> {
+    let mut word = [ControlByte::Dummy as u8];
+    bus.transfer_in_place(&mut word)
+        .await
+        .map_err(BusError::transfer)?;
+    Ok(word[0])
+}
@@ -0,0 +1,187 @@
+//! Async versions of sending/receiving data to/from a TCP server.
+//!
+//! ## Usage
+//!
+//! ```no_run
+//! let mut connection = TcpClient::build(&mut wifi)
+//!     .open_hostname("github.com", 443, TransportMode::Tls, &mut delay)
+//!     .await?;
+//!
+//! connection.send_all(http_document.as_bytes(), &mut delay).await?;
+//!
+//! let mut response = [0u8; 512];
+//! let length = connection.receive(&mut response).await?;
+//! defmt::info!("Response: {:?}", &response[..length]);
+//!
+//! connection.close().await?;
+//! ```
+//!
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::spi::SpiDevice;
+
+use super::gpio::EspControlInterface;
+use super::wifi::Wifi;
+use super::NinaProtocolHandler;
+use crate::network::{ConnectionState, Hostname, IpAddress, Port, Socket, TransportMode};
+use crate::tcp_client::{Establishing, SendAll, ESTABLISH_POLL_INTERVAL_MS, START_CLIENT_DELAY_MS};
+use crate::Error;
+
+/// A client type that connects to a remote server using the TCP protocol from async code.
+pub struct TcpClient<'a, S, C> {
+    pub(crate) protocol_handler: &'a mut NinaProtocolHandler<S, C>,
+}
+
+impl<'a, S, C> TcpClient<'a, S, C>
+where
+    S: SpiDevice,
+    C: EspControlInterface,
+{
+    /// Build a new instance of a [`TcpClient`] provided a [`Wifi`] instance.
+    pub fn build(wifi: &'a mut Wifi<S, C>) -> Self {
+        Self {
+            protocol_handler: &mut wifi.protocol_handler,
+        }
+    }
+
+    /// Open a connection to the server at `ip` on `port` using transport layer `mode`.
+    pub async fn open<D: DelayNs>(
+        self,
+        ip: IpAddress,
+        port: Port,
+        mode: TransportMode,
+        delay: &mut D,
+    ) -> Result<TcpConnection<'a, S, C>, Error> {
+        let socket = self.protocol_handler.get_socket().await?;
+        self.protocol_handler
+            .start_client_tcp(socket, ip, port, &mode)
+            .await?;
+
+        self.establish(socket, mode, delay).await
+    }
+
+    /// Open a connection to the server named `hostname` on `port` using transport layer
+    /// `mode`. In TLS modes, the ESP32 target resolves the hostname itself as it needs it to
+    /// verify the server's certificate.
+    pub async fn open_hostname<D: DelayNs>(
+        self,
+        hostname: Hostname<'_>,
+        port: Port,
+        mode: TransportMode,
+        delay: &mut D,
+    ) -> Result<TcpConnection<'a, S, C>, Error> {
+        let socket = self.protocol_handler.get_socket().await?;
+        if mode.is_tls() {
+            self.protocol_handler
+                .start_client_tcp_hostname(socket, hostname, port, &mode)
+                .await?;
+        } else {
+            let ip = self.protocol_handler.resolve(hostname).await?;
+            self.protocol_handler
+                .start_client_tcp(socket, ip, port, &mode)
+                .await?;
+        }
+
+        self.establish(socket, mode, delay).await
+    }
+
+    // Waits for the connection started on `socket` to be established. The socket is
+    // stopped again if the connection can't be established.
+    async fn establish<D: DelayNs>(
+        self,
+        socket: Socket,
+        mode: TransportMode,
+        delay: &mut D,
+    ) -> Result<TcpConnection<'a, S, C>, Error> {
+        delay.delay_ms(START_CLIENT_DELAY_MS.into()).await;
+
+        let mut establishing = Establishing::new();
+
+        loop {
+            let state = self.protocol_handler.get_client_state_tcp(socket).await;
+            match establishing.check(state) {
+                Ok(true) => {
+                    return Ok(TcpConnection {
+                        protocol_handler: self.protocol_handler,
+                        socket,
+                        mode,
+                    });
+                }
+                Ok(false) => delay.delay_ms(ESTABLISH_POLL_INTERVAL_MS.into()).await,
+                Err(error) => {
+                    self.protocol_handler.stop_client_tcp(socket).await?;
+
+                    return Err(error);
+                }
+            }
+        }
+    }
+}
+
+/// An open connection to a remote server returned by [`TcpClient::open`] or
+/// [`TcpClient::open_hostname`]. Unlike the blocking
+/// [`TcpConnection`](crate::tcp_client::TcpConnection), it can't close itself when dropped,
+/// so call [`TcpConnection::close`] to free up its socket on the ESP32 target.
+pub struct TcpConnection<'a, S, C> {
+    pub(crate) protocol_handler: &'a mut NinaProtocolHandler<S, C>,
+    pub(crate) socket: Socket,
+    pub(crate) mode: TransportMode,
+}
+
+impl<'a, S, C> TcpConnection<'a, S, C>
+where
+    S: SpiDevice,
+    C: EspControlInterface,
+{
+    /// Send up to 1024 bytes of `data` to the connected server, returning the number of bytes
+    /// the ESP32 target wrote. `delay` paces the checks that the data was sent.
+    pub async fn send<D: DelayNs>(&mut self, data: &[u8], delay: &mut D) -> Result<usize, Error> {
+        self.protocol_handler
+            .send_data(data, self.socket, delay)
+            .await
+    }
+
+    /// Send all of `data` to the connected server, however long it is, the same way as the
+    /// blocking [`TcpClient::send_all`](crate::tcp_client::TcpClient::send_all).
+    pub async fn send_all<D: DelayNs>(&mut self, data: &[u8], delay: &mut D) -> Result<(), Error> {
+        let mut send_all = SendAll::new(data);
+
+        while let Some(frame) = send_all.next_frame() {
+            let written = self
+                .protocol_handler
+                .send_data(frame, self.socket, delay)
+                .await?;
+            send_all.sent(written)?;
+        }
+
+        Ok(())
+    }
+
+    /// Receive data sent by the connected server into `data`, returning the number of bytes
+    /// read. Returns 0 right away if no data is available yet.
+    pub async fn receive(&mut self, data: &mut [u8]) -> Result<usize, Error> {
+        self.protocol_handler.receive_data(self.socket, data).await
+    }
+
+    /// Get the number of bytes sent by the connected server that are ready to be read.
+    pub async fn available(&mut self) -> Result<usize, Error> {
+        self.protocol_handler.avail_data_tcp(self.socket).await
+    }
+
+    /// Get the current [`ConnectionState`] of the connection.
+    pub async fn state(&mut self) -> Result<ConnectionState, Error> {
+        self.protocol_handler
+            .get_client_state_tcp(self.socket)
+            .await
+    }
+
+    /// Get the [`TransportMode`] the connection was opened with.
+    pub fn mode(&self) -> TransportMode {
+        self.mode
+    }
+
+    /// Close the connection to the server.
+    pub async fn close(self) -> Result<(), Error> {
+        self.protocol_handler.stop_client_tcp(self.socket).await
+    }
+}
@@ -0,0 +1,92 @@
+//! Async versions of the core WiFi functions, such as joining a network and resolving
+//! a DNS hostname.
+//!
+//! ## Usage
+//!
+//! ```no_run
+//! let mut wifi = Wifi::init(spi_device, esp_pins, &mut delay).await?;
+//!
+//! wifi.join(SSID, PASSPHRASE).await?;
+//! while wifi.get_connection_status().await? != ConnectionStatus::Connected {
+//!     Timer::after_millis(100).await;
+//! }
+//!
+//! let ip_address = wifi.resolve("example.com").await?;
+//! ```
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::spi::SpiDevice;
+
+use super::gpio::EspControlInterface;
+use super::NinaProtocolHandler;
+use crate::network::IpAddress;
+use crate::protocol::ClientCredentials;
+use crate::wifi::{validate_join, ConnectionStatus, JoinCredentials};
+use crate::{Error, FirmwareVersion};
+
+/// Base type for controlling an ESP32-WROOM NINA firmware-based WiFi board from async code.
+#[derive(Debug)]
+pub struct Wifi<S, C> {
+    pub(crate) protocol_handler: NinaProtocolHandler<S, C>,
+}
+
+impl<S, C> Wifi<S, C>
+where
+    S: SpiDevice,
+    C: EspControlInterface,
+{
+    /// Initialize the ESP32-WROOM WiFi device.
+    /// Call this function to put the connected ESP32-WROOM device in a known good state to accept commands.
+    pub async fn init<D: DelayNs>(
+        spi: S,
+        esp32_control_pins: C,
+        delay: &mut D,
+    ) -> Result<Wifi<S, C>, Error> {
+        let mut wifi = Wifi {
+            protocol_handler: NinaProtocolHandler {
+                bus: spi,
+                control_pins: esp32_control_pins,
+                client_credentials: ClientCredentials::default(),
+            },
+        };
+
+        wifi.protocol_handler.init()?;
+        wifi.protocol_handler.reset(delay).await?;
+        Ok(wifi)
+    }
+
+    /// Retrieve the NINA firmware version contained on the connected ESP32-WROOM device (e.g. 1.7.4).
+    pub async fn firmware_version(&mut self) -> Result<FirmwareVersion, Error> {
+        self.protocol_handler.get_fw_version().await
+    }
+
+    /// Join a WiFi network given an SSID and a Passphrase. The SSID and passphrase are checked
+    /// before anything is sent to the ESP32-WROOM device, returning
+    /// `NetworkError::InvalidCredentials` if they can't be valid. Poll
+    /// [`Wifi::get_connection_status`] to know when the network has been joined.
+    pub async fn join(&mut self, ssid: &str, passphrase: &str) -> Result<(), Error> {
+        validate_join(ssid, &JoinCredentials::Wpa(passphrase))?;
+
+        self.protocol_handler.set_passphrase(ssid, passphrase).await
+    }
+
+    /// Disconnect from a previously joined WiFi network.
+    pub async fn leave(&mut self) -> Result<(), Error> {
+        self.protocol_handler.disconnect().await
+    }
+
+    /// Retrieve the current WiFi network [`ConnectionStatus`].
+    pub async fn get_connection_status(&mut self) -> Result<ConnectionStatus, Error> {
+        self.protocol_handler.get_conn_status().await
+    }
+
+    /// Query the DNS server(s) for the associated IP address to the provided hostname.
+    pub async fn resolve(&mut self, hostname: &str) -> Result<IpAddress, Error> {
+        self.protocol_handler.resolve(hostname).await
+    }
+
+    /// Return the `SpiDevice` instance typically used when cleaning up an instance of [`Wifi`].
+    pub fn destroy(self) -> S {
+        self.protocol_handler.bus
+    }
+}
@@ -0,0 +1,272 @@
+//! Keep a WiFi network joined, rejoining it after the connection is lost.
+//!
+//! ## Usage
+//!
+//! ```no_run
+//! let wifi = Wifi::init(spi, esp_pins, &mut delay).unwrap();
+//! let mut manager = ConnectionManager::new(wifi, SSID, JoinCredentials::Wpa(PASSPHRASE));
+//!
+//! loop {
+//!     // Any millisecond clock works as long as it wraps at u32::MAX. Truncating the 64-bit
+//!     // microsecond counter does; dividing the 32-bit one wouldn't, it wraps every 71 minutes.
+//!     let now_ms = (timer.get_counter() / 1000) as u32;
+//!     match manager.poll(now_ms, &mut delay) {
+//!         Ok(Some(ConnectionEvent::Connected)) => defmt::info!("Connected to {:?}", SSID),
+//!         Ok(Some(event)) => defmt::info!("Connection event: {:?}", event),
+//!         Ok(None) => {}
+//!         Err(e) => defmt::error!("Failed to poll connection: {:?}", e),
+//!     }
+//!
+//!     if manager.is_connected() {
+//!         let mut tcp_client = TcpClient::build(manager.wifi());
+//!         // ...
+//!     }
+//! }
+//! ```
+//!
+
+use core::fmt::Debug;
+
+use defmt::{write, Format, Formatter};
+
+use embedded_hal::blocking::{delay::DelayMs, spi::Transfer};
+
+use super::gpio::EspControlInterface;
+use super::network::NetworkError;
+use super::wifi::{ConnectionStatus, JoinCredentials, Wifi};
+use super::Error;
+
+/// The default time in milliseconds to wait for a join attempt to succeed.
+pub const DEFAULT_JOIN_TIMEOUT_MS: u32 = 10_000;
+
+/// The default time in milliseconds to wait before retrying after the first failed join
+/// attempt. It doubles after each further failure.
+pub const DEFAULT_INITIAL_BACKOFF_MS: u32 = 1_000;
+
+/// The default upper limit in milliseconds on the time to wait between join attempts.
+pub const DEFAULT_MAX_BACKOFF_MS: u32 = 60_000;
+
+/// The default number of consecutive failed join attempts after which the ESP32 target is
+/// reset.
+pub const DEFAULT_MAX_FAILURES: u32 = 5;
+
+/// A change in the connection to the WiFi network reported by [`ConnectionManager::poll`].
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum ConnectionEvent {
+    /// The network was joined
+    Connected,
+    /// The connection to the network was lost, it will be rejoined by the next poll
+    Disconnected,
+    /// An attempt to join the network failed, it will be retried after `retry_in_ms`
+    JoinFailed {
+        /// Number of consecutive failed attempts
+        attempts: u32,
+        /// Time in milliseconds until the next attempt
+        retry_in_ms: u32,
+    },
+    /// The ESP32 target was reset after too many failed attempts to join the network
+    Reset,
+}
+
+impl Format for ConnectionEvent {
+    fn format(&self, fmt: Formatter) {
+        match self {
+            ConnectionEvent::Connected => write!(fmt, "Connected to WiFi network"),
+            ConnectionEvent::Disconnected => write!(fmt, "Disconnected from WiFi network"),
+            ConnectionEvent::JoinFailed {
+                attempts,
+                retry_in_ms,
+            } => write!(
+                fmt,
+                "Failed to join WiFi network {} time(s), retrying in {} ms",
+                attempts, retry_in_ms
+            ),
+            ConnectionEvent::Reset => write!(fmt, "Reset ESP32 target"),
+        }
+    }
+}
+
+// Where the manager is in joining the network
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+enum State {
+    Disconnected,
+    Joining { started_ms: u32 },
+    Connected,
+    BackingOff { started_ms: u32, duration_ms: u32 },
+}
+
+/// Wraps a [`Wifi`] instance to keep a WiFi network joined. Each call to
+/// [`ConnectionManager::poll`] checks on the connection and starts the next join attempt
+/// when one is due, backing off exponentially between failed attempts and resetting the
+/// ESP32 target after too many of them.
+pub struct ConnectionManager<'a, B, C> {
+    wifi: Wifi<B, C>,
+    ssid: &'a str,
+    credentials: JoinCredentials<'a>,
+    state: State,
+    failures: u32,
+    join_timeout_ms: u32,
+    initial_backoff_ms: u32,
+    max_backoff_ms: u32,
+    max_failures: u32,
+}
+
+impl<'a, B, C> ConnectionManager<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    /// Create a [`ConnectionManager`] that keeps `wifi` joined to the network named `ssid`
+    /// using `credentials`. Nothing is sent to the ESP32 target until the first poll.
+    pub fn new(wifi: Wifi<B, C>, ssid: &'a str, credentials: JoinCredentials<'a>) -> Self {
+        Self {
+            wifi,
+            ssid,
+            credentials,
+            state: State::Disconnected,
+            failures: 0,
+            join_timeout_ms: DEFAULT_JOIN_TIMEOUT_MS,
+            initial_backoff_ms: DEFAULT_INITIAL_BACKOFF_MS,
+            max_backoff_ms: DEFAULT_MAX_BACKOFF_MS,
+            max_failures: DEFAULT_MAX_FAILURES,
+        }
+    }
+
+    /// Set how long in milliseconds to wait for a join attempt to succeed before counting it
+    /// as failed. Defaults to [`DEFAULT_JOIN_TIMEOUT_MS`].
+    pub fn set_join_timeout(&mut self, timeout_ms: u32) {
+        self.join_timeout_ms = timeout_ms;
+    }
+
+    /// Set the time in milliseconds to wait after the first failed join attempt, doubling
+    /// after each further failure up to `max_ms`. Defaults to [`DEFAULT_INITIAL_BACKOFF_MS`]
+    /// and [`DEFAULT_MAX_BACKOFF_MS`].
+    pub fn set_backoff(&mut self, initial_ms: u32, max_ms: u32) {
+        self.initial_backoff_ms = initial_ms;
+        self.max_backoff_ms = max_ms;
+    }
+
+    /// Set the number of consecutive failed join attempts after which the ESP32 target is
+    /// reset. Defaults to [`DEFAULT_MAX_FAILURES`].
+    pub fn set_max_failures(&mut self, max_failures: u32) {
+        self.max_failures = max_failures;
+    }
+
+    /// Check on the connection to the WiFi network and start a join attempt if one is due,
+    /// returning a [`ConnectionEvent`] when something changed. `now_ms` is the current time
+    /// in milliseconds from any monotonic clock that wraps around at `u32::MAX`. Never waits,
+    /// except for `delay` being used to reset the ESP32 target after too many failed attempts.
+    ///
+    /// Errors talking to the ESP32 target while joining count as a failed join attempt, so
+    /// they are backed off from and eventually lead to a reset. Only invalid credentials,
+    /// which no retry can fix, and a failed reset are returned as errors.
+    pub fn poll<D: DelayMs<u16>>(
+        &mut self,
+        now_ms: u32,
+        delay: &mut D,
+    ) -> Result<Option<ConnectionEvent>, Error> {
+        match self.state {
+            State::Disconnected => self.start_join(now_ms, delay),
+            State::Joining { started_ms } => match self.wifi.get_connection_status() {
+                Ok(ConnectionStatus::Connected) => {
+                    self.state = State::Connected;
+                    self.failures = 0;
+                    Ok(Some(ConnectionEvent::Connected))
+                }
+                Ok(ConnectionStatus::Failed) | Err(_) => self.join_failed(now_ms, delay),
+                _ if now_ms.wrapping_sub(started_ms) >= self.join_timeout_ms => {
+                    self.join_failed(now_ms, delay)
+                }
+                _ => Ok(None),
+            },
+            // An ESP32 target that can't report its status is no better than a lost
+            // connection, and rejoining is what eventually resets it
+            State::Connected => match self.wifi.get_connection_status() {
+                Ok(ConnectionStatus::Connected) => Ok(None),
+                _ => {
+                    self.state = State::Disconnected;
+                    Ok(Some(ConnectionEvent::Disconnected))
+                }
+            },
+            State::BackingOff {
+                started_ms,
+                duration_ms,
+            } => {
+                if now_ms.wrapping_sub(started_ms) >= duration_ms {
+                    self.start_join(now_ms, delay)
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    /// Check whether the WiFi network was joined as of the last poll.
+    pub fn is_connected(&self) -> bool {
+        self.state == State::Connected
+    }
+
+    /// Get the wrapped [`Wifi`] instance, e.g. to build a
+    /// [`TcpClient`](super::tcp_client::TcpClient) with.
+    pub fn wifi(&mut self) -> &mut Wifi<B, C> {
+        &mut self.wifi
+    }
+
+    /// Stop managing the connection and return the wrapped [`Wifi`] instance.
+    pub fn release(self) -> Wifi<B, C> {
+        self.wifi
+    }
+
+    // Sends the credentials to the ESP32 target and starts timing the join attempt. Failing
+    // to send them counts as a failed attempt.
+    fn start_join<D: DelayMs<u16>>(
+        &mut self,
+        now_ms: u32,
+        delay: &mut D,
+    ) -> Result<Option<ConnectionEvent>, Error> {
+        match self.wifi.connect(self.ssid, self.credentials) {
+            Ok(()) => {
+                self.state = State::Joining { started_ms: now_ms };
+                Ok(None)
+            }
+            Err(Error::Network(NetworkError::InvalidCredentials)) => {
+                Err(NetworkError::InvalidCredentials.into())
+            }
+            Err(_) => self.join_failed(now_ms, delay),
+        }
+    }
+
+    // Counts a failed join attempt, either backing off before the next one or resetting
+    // the ESP32 target once there have been too many.
+    fn join_failed<D: DelayMs<u16>>(
+        &mut self,
+        now_ms: u32,
+        delay: &mut D,
+    ) -> Result<Option<ConnectionEvent>, Error> {
+        self.failures += 1;
+
+        if self.failures >= self.max_failures {
+            self.failures = 0;
+            self.wifi.reset(delay)?;
+            self.state = State::Disconnected;
+
+            return Ok(Some(ConnectionEvent::Reset));
+        }
+
+        let multiplier = 1u32.checked_shl(self.failures - 1).unwrap_or(u32::MAX);
+        let retry_in_ms = self
+            .initial_backoff_ms
+            .saturating_mul(multiplier)
+            .min(self.max_backoff_ms);
+        self.state = State::BackingOff {
+            started_ms: now_ms,
+            duration_ms: retry_in_ms,
+        };
+
+        Ok(Some(ConnectionEvent::JoinFailed {
+            attempts: self.failures,
+            retry_in_ms,
+        }))
+    }
+}
@@ -28,18 +28,27 @@
 //!     // ACK on pin x (GPIO10)
 //!     ack: pins.gpio10.into_mode::<hal::gpio::FloatingInput>(),
 //! };
+//!
+//! // Optionally give up waiting on an unresponsive ESP32 target instead of blocking forever.
+//! // Any `DelayUs<u16>` implementation that is not needed elsewhere can drive the deadline.
+//! let esp_pins = esp32_wroom_rp::gpio::TimeoutEspControl::new(esp_pins, timer_delay);
 //! ```
 
+use core::fmt::Debug;
 use core::hint;
 
-use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::blocking::delay::{DelayMs, DelayUs};
 use embedded_hal::digital::v2::{InputPin, OutputPin};
 
-#[allow(dead_code)]
-#[derive(Clone, Copy, Debug)]
-enum IOError {
-    Pin,
-}
+use super::protocol::ProtocolError;
+use super::{BusError, Error};
+
+/// The default time in milliseconds to wait for the NINA firmware to become ready
+/// for each protocol command before giving up.
+pub const DEFAULT_COMMAND_TIMEOUT_MS: u32 = 10_000;
+
+// How often the ready/ack lines are polled while waiting on a deadline
+const POLL_INTERVAL_US: u16 = 100;
 
 /// Provides an internal pin interface that abstracts the extra control lines that
 /// are separate from a data bus (e.g. SPI/I2C).
@@ -47,31 +56,65 @@ enum IOError {
 /// Not meant to be used outside of the crate.
 pub trait EspControlInterface {
     /// Initializes all controls pins to set ready communication with the NINA firmware.
-    fn init(&mut self);
+    fn init(&mut self) -> Result<(), Error>;
 
     /// Resets communication with the NINA firmware.
-    fn reset<D: DelayMs<u16>>(&mut self, delay: &mut D);
+    fn reset<D: DelayMs<u16>>(&mut self, delay: &mut D) -> Result<(), Error>;
 
     /// Tells the NINA firmware we're about to send it a protocol command.
-    fn esp_select(&mut self);
+    fn esp_select(&mut self) -> Result<(), Error>;
 
     /// Tells the NINA firmware we're done sending it a protocol command.
-    fn esp_deselect(&mut self);
+    fn esp_deselect(&mut self) -> Result<(), Error>;
 
     /// Is the NINA firmware ready to send it a protocol command?
-    fn get_esp_ready(&self) -> bool;
+    fn get_esp_ready(&self) -> Result<bool, Error>;
 
     /// Is the NINA firmware ready to receive more commands? Also referred to as BUSY.
-    fn get_esp_ack(&self) -> bool;
+    fn get_esp_ack(&self) -> Result<bool, Error>;
 
     /// Blocking waits for the NINA firmware to be ready to send it a protocol command.
-    fn wait_for_esp_ready(&self);
+    fn wait_for_esp_ready(&self) -> Result<(), Error>;
 
     /// Blocking waits for the NINA firmware to acknowledge it's ready to receive more commands.
-    fn wait_for_esp_ack(&self);
+    fn wait_for_esp_ack(&self) -> Result<(), Error>;
 
     /// Blocking waits for the NINA firmware to be ready to send it a protocol command.
-    fn wait_for_esp_select(&mut self);
+    fn wait_for_esp_select(&mut self) -> Result<(), Error>;
+
+    /// Waits up to `timeout_ms` milliseconds for the NINA firmware to be ready to send it a
+    /// protocol command, returning [`ProtocolError::CommunicationTimeout`] otherwise.
+    ///
+    /// The default implementation has no time source and blocks like `wait_for_esp_ready()`.
+    fn wait_for_esp_ready_timeout(&mut self, _timeout_ms: u32) -> Result<(), Error> {
+        self.wait_for_esp_ready()
+    }
+
+    /// Waits up to `timeout_ms` milliseconds for the NINA firmware to acknowledge it's ready
+    /// to receive more commands, returning [`ProtocolError::CommunicationTimeout`] otherwise.
+    ///
+    /// The default implementation has no time source and blocks like `wait_for_esp_ack()`.
+    fn wait_for_esp_ack_timeout(&mut self, _timeout_ms: u32) -> Result<(), Error> {
+        self.wait_for_esp_ack()
+    }
+
+    /// Waits `ms` milliseconds before polling the NINA firmware again, e.g. for data to arrive
+    /// on a socket.
+    ///
+    /// The default implementation has no time source and returns right away.
+    fn delay_ms(&mut self, _ms: u16) {}
+
+    /// Waits up to `timeout_ms` milliseconds for each of the ready and ack lines while selecting
+    /// the NINA firmware. The NINA firmware is deselected again if it never acknowledges.
+    fn wait_for_esp_select_timeout(&mut self, timeout_ms: u32) -> Result<(), Error> {
+        self.wait_for_esp_ready_timeout(timeout_ms)?;
+        self.esp_select()?;
+        if let Err(error) = self.wait_for_esp_ack_timeout(timeout_ms) {
+            self.esp_deselect()?;
+            return Err(error);
+        }
+        Ok(())
+    }
 }
 
 /// A structured representation of all GPIO pins that control a ESP32-WROOM NINA firmware-based
@@ -94,59 +137,169 @@ pub struct EspControlPins<CS, GPIO0, RESETN, ACK> {
 impl<CS, GPIO0, RESETN, ACK> EspControlInterface for EspControlPins<CS, GPIO0, RESETN, ACK>
 where
     CS: OutputPin,
+    CS::Error: Debug,
     GPIO0: OutputPin,
+    GPIO0::Error: Debug,
     RESETN: OutputPin,
+    RESETN::Error: Debug,
     ACK: InputPin,
+    ACK::Error: Debug,
 {
-    fn init(&mut self) {
+    fn init(&mut self) -> Result<(), Error> {
         // Chip select is active-low, so we'll initialize it to a driven-high state
-        self.cs.set_high().ok();
-        self.gpio0.set_high().ok();
-        self.resetn.set_high().ok();
-        self.get_esp_ready();
+        self.cs.set_high().map_err(BusError::control_pin)?;
+        self.gpio0.set_high().map_err(BusError::control_pin)?;
+        self.resetn.set_high().map_err(BusError::control_pin)?;
+        self.get_esp_ready()?;
+        Ok(())
     }
 
-    fn reset<D: DelayMs<u16>>(&mut self, delay: &mut D) {
-        self.gpio0.set_high().ok();
-        self.cs.set_high().ok();
-        self.resetn.set_low().ok();
+    fn reset<D: DelayMs<u16>>(&mut self, delay: &mut D) -> Result<(), Error> {
+        self.gpio0.set_high().map_err(BusError::control_pin)?;
+        self.cs.set_high().map_err(BusError::control_pin)?;
+        self.resetn.set_low().map_err(BusError::control_pin)?;
         delay.delay_ms(10);
-        self.resetn.set_high().ok();
+        self.resetn.set_high().map_err(BusError::control_pin)?;
         delay.delay_ms(750);
+        Ok(())
     }
 
-    fn esp_select(&mut self) {
-        self.cs.set_low().ok();
+    fn esp_select(&mut self) -> Result<(), Error> {
+        self.cs.set_low().map_err(BusError::control_pin)?;
+        Ok(())
     }
 
-    fn esp_deselect(&mut self) {
-        self.cs.set_high().ok();
+    fn esp_deselect(&mut self) -> Result<(), Error> {
+        self.cs.set_high().map_err(BusError::control_pin)?;
+        Ok(())
     }
 
-    fn get_esp_ready(&self) -> bool {
-        self.ack.is_low().ok().unwrap()
+    fn get_esp_ready(&self) -> Result<bool, Error> {
+        Ok(self.ack.is_low().map_err(BusError::control_pin)?)
     }
 
-    fn get_esp_ack(&self) -> bool {
-        self.ack.is_high().ok().unwrap()
+    fn get_esp_ack(&self) -> Result<bool, Error> {
+        Ok(self.ack.is_high().map_err(BusError::control_pin)?)
     }
 
-    fn wait_for_esp_ready(&self) {
-        while !self.get_esp_ready() {
+    fn wait_for_esp_ready(&self) -> Result<(), Error> {
+        while !self.get_esp_ready()? {
             hint::spin_loop(); // Make sure rustc doesn't optimize this loop out
         }
+        Ok(())
     }
 
-    fn wait_for_esp_ack(&self) {
-        while !self.get_esp_ack() {
+    fn wait_for_esp_ack(&self) -> Result<(), Error> {
+        while !self.get_esp_ack()? {
             hint::spin_loop(); // Make sure rustc doesn't optimize this loop out
         }
+        Ok(())
+    }
+
+    fn wait_for_esp_select(&mut self) -> Result<(), Error> {
+        self.wait_for_esp_ready()?;
+        self.esp_select()?;
+        self.wait_for_esp_ack()
+    }
+}
+
+/// Wraps an [`EspControlInterface`] (e.g. [`EspControlPins`]) together with a delay that is
+/// used to bound how long to wait on the NINA firmware's ready/ack lines. Without it, an
+/// unplugged or crashed ESP32 target will block forever. The delay also paces polling the
+/// NINA firmware, e.g. while waiting for data to arrive on a socket. Pass a single instance of this
+/// struct into `Wifi::init()` in place of the wrapped control pins.
+pub struct TimeoutEspControl<C, D> {
+    /// The control pins of the ESP32 target.
+    pub pins: C,
+    /// Paces the polling of the ready/ack lines and of the NINA firmware, and measures the
+    /// elapsed time.
+    pub delay: D,
+}
+
+impl<C, D> TimeoutEspControl<C, D>
+where
+    C: EspControlInterface,
+    D: DelayUs<u16>,
+{
+    /// Build a new instance of a [`TimeoutEspControl`] from a set of control pins and a delay.
+    pub fn new(pins: C, delay: D) -> Self {
+        Self { pins, delay }
     }
 
-    fn wait_for_esp_select(&mut self) {
-        self.wait_for_esp_ready();
-        self.esp_select();
-        self.wait_for_esp_ack();
+    // Polls `line_is_set` until it reports true or `timeout_ms` milliseconds have elapsed.
+    fn poll_until<F: Fn(&C) -> Result<bool, Error>>(
+        &mut self,
+        timeout_ms: u32,
+        line_is_set: F,
+    ) -> Result<(), Error> {
+        let polls = timeout_ms.saturating_mul(1_000 / POLL_INTERVAL_US as u32);
+
+        if line_is_set(&self.pins)? {
+            return Ok(());
+        }
+        for _ in 0..polls {
+            self.delay.delay_us(POLL_INTERVAL_US);
+            if line_is_set(&self.pins)? {
+                return Ok(());
+            }
+        }
+        Err(ProtocolError::CommunicationTimeout.into())
+    }
+}
+
+impl<C, D> EspControlInterface for TimeoutEspControl<C, D>
+where
+    C: EspControlInterface,
+    D: DelayUs<u16>,
+{
+    fn init(&mut self) -> Result<(), Error> {
+        self.pins.init()
+    }
+
+    fn reset<DM: DelayMs<u16>>(&mut self, delay: &mut DM) -> Result<(), Error> {
+        self.pins.reset(delay)
+    }
+
+    fn esp_select(&mut self) -> Result<(), Error> {
+        self.pins.esp_select()
+    }
+
+    fn esp_deselect(&mut self) -> Result<(), Error> {
+        self.pins.esp_deselect()
+    }
+
+    fn get_esp_ready(&self) -> Result<bool, Error> {
+        self.pins.get_esp_ready()
+    }
+
+    fn get_esp_ack(&self) -> Result<bool, Error> {
+        self.pins.get_esp_ack()
+    }
+
+    fn wait_for_esp_ready(&self) -> Result<(), Error> {
+        self.pins.wait_for_esp_ready()
+    }
+
+    fn wait_for_esp_ack(&self) -> Result<(), Error> {
+        self.pins.wait_for_esp_ack()
+    }
+
+    fn wait_for_esp_select(&mut self) -> Result<(), Error> {
+        self.pins.wait_for_esp_select()
+    }
+
+    fn wait_for_esp_ready_timeout(&mut self, timeout_ms: u32) -> Result<(), Error> {
+        self.poll_until(timeout_ms, |pins| pins.get_esp_ready())
+    }
+
+    fn wait_for_esp_ack_timeout(&mut self, timeout_ms: u32) -> Result<(), Error> {
+        self.poll_until(timeout_ms, |pins| pins.get_esp_ack())
+    }
+
+    fn delay_ms(&mut self, ms: u16) {
+        for _ in 0..ms {
+            self.delay.delay_us(1_000);
+        }
     }
 }
 
@@ -163,8 +316,11 @@ impl Default for EspControlPins<(), (), (), ()> {
 
 #[cfg(test)]
 mod gpio_tests {
-    use super::EspControlPins;
+    use super::{EspControlPins, TimeoutEspControl};
     use crate::gpio::EspControlInterface;
+    use crate::protocol::ProtocolError;
+    use crate::Error;
+    use embedded_hal_mock::delay::MockNoop;
     use embedded_hal_mock::pin::{
         Mock as PinMock, State as PinState, Transaction as PinTransaction,
     };
@@ -190,11 +346,72 @@ mod gpio_tests {
             ack: ack_mock,
         };
 
-        pins.init();
+        pins.init().unwrap();
 
         pins.cs.done();
         pins.gpio0.done();
         pins.resetn.done();
         pins.ack.done();
     }
+
+    fn pins_with_ack(
+        ack_expectations: &[PinTransaction],
+    ) -> EspControlPins<PinMock, PinMock, PinMock, PinMock> {
+        EspControlPins {
+            cs: PinMock::new(&[]),
+            gpio0: PinMock::new(&[]),
+            resetn: PinMock::new(&[]),
+            ack: PinMock::new(ack_expectations),
+        }
+    }
+
+    #[test]
+    fn wait_for_esp_ready_timeout_returns_once_ready() {
+        let ack_expectations = [
+            PinTransaction::get(PinState::High),
+            PinTransaction::get(PinState::High),
+            PinTransaction::get(PinState::Low),
+        ];
+        let mut control = TimeoutEspControl::new(pins_with_ack(&ack_expectations), MockNoop::new());
+
+        control.wait_for_esp_ready_timeout(1).unwrap();
+
+        control.pins.ack.done();
+    }
+
+    #[test]
+    fn wait_for_esp_ready_timeout_gives_up_after_deadline() {
+        // A 1 ms deadline polling every 100 us reads the ready line 1 + 10 times
+        let ack_expectations = vec![PinTransaction::get(PinState::High); 11];
+        let mut control = TimeoutEspControl::new(pins_with_ack(&ack_expectations), MockNoop::new());
+
+        assert_eq!(
+            control.wait_for_esp_ready_timeout(1).unwrap_err(),
+            Error::Protocol(ProtocolError::CommunicationTimeout)
+        );
+
+        control.pins.ack.done();
+    }
+
+    #[test]
+    fn wait_for_esp_select_timeout_deselects_when_never_acknowledged() {
+        let cs_expectations = [
+            PinTransaction::set(PinState::Low),
+            PinTransaction::set(PinState::High),
+        ];
+        // Ready on the first read, then never acknowledged within the deadline
+        let ack_expectations = vec![PinTransaction::get(PinState::Low); 1 + 11];
+
+        let mut pins = pins_with_ack(&ack_expectations);
+        pins.cs = PinMock::new(&cs_expectations);
+        let mut control = TimeoutEspControl::new(pins, MockNoop::new());
+
+        assert_eq!(
+            control.wait_for_esp_select_timeout(1).unwrap_err(),
+            Error::Protocol(ProtocolError::CommunicationTimeout)
+        );
+
+        control.pins.cs.done();
+        control.pins.ack.done();
+    }
 }
@@ -149,16 +149,30 @@
 #![warn(missing_docs)]
 #![cfg_attr(not(test), no_std)]
 
+#[cfg(feature = "async")]
+pub mod asynch;
+pub mod connection_manager;
 pub mod gpio;
 pub mod network;
+pub mod network_stack;
 pub mod protocol;
 pub mod tcp_client;
+pub mod tcp_server;
+pub mod udp_socket;
 pub mod wifi;
 
 mod spi;
 
+use core::fmt::{self, Debug};
+
 use defmt::{write, Format, Formatter};
 
+use embedded_io::ErrorKind;
+
+use embedded_nal::{TcpError, TcpErrorKind};
+
+use heapless::String;
+
 use network::NetworkError;
 
 use protocol::ProtocolError;
@@ -167,7 +181,7 @@ use protocol::ProtocolError;
 #[derive(Debug, Eq, PartialEq)]
 pub enum Error {
     /// SPI/I2C related communications error with the ESP32 WiFi target
-    Bus,
+    Bus(BusError),
     /// Protocol error in communicating with the ESP32 WiFi target
     Protocol(ProtocolError),
 
@@ -178,7 +192,7 @@ pub enum Error {
 impl Format for Error {
     fn format(&self, fmt: Formatter) {
         match self {
-            Error::Bus => write!(fmt, "Bus error"),
+            Error::Bus(e) => write!(fmt, "Bus error: {}", e),
             Error::Protocol(e) => write!(
                 fmt,
                 "Communication protocol error with ESP32 WiFi target: {}",
@@ -189,6 +203,115 @@ impl Format for Error {
     }
 }
 
+impl embedded_io::Error for Error {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Network(NetworkError::ConnectionClosed) => ErrorKind::BrokenPipe,
+            Error::Network(NetworkError::ConnectFailed) => ErrorKind::ConnectionRefused,
+            Error::Network(NetworkError::ConnectionTimeout)
+            | Error::Protocol(ProtocolError::CommunicationTimeout) => ErrorKind::TimedOut,
+            Error::Network(NetworkError::Unsupported) => ErrorKind::Unsupported,
+            Error::Protocol(ProtocolError::PayloadTooLarge) => ErrorKind::InvalidInput,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl TcpError for Error {
+    fn kind(&self) -> TcpErrorKind {
+        match self {
+            Error::Network(NetworkError::ConnectionClosed) => TcpErrorKind::PipeClosed,
+            _ => TcpErrorKind::Other,
+        }
+    }
+}
+
+/// Errors that occur while driving the data bus or control pins connected to the
+/// ESP32 WiFi target. Each carries the error returned by the HAL.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum BusError {
+    /// A transfer over the data bus (e.g. SPI) failed.
+    Transfer(HalError),
+    /// Setting or reading one of the GPIO control pins failed.
+    ControlPin(HalError),
+}
+
+impl BusError {
+    pub(crate) fn transfer<E: Debug>(error: E) -> Self {
+        BusError::Transfer(HalError::new(&error))
+    }
+
+    pub(crate) fn control_pin<E: Debug>(error: E) -> Self {
+        BusError::ControlPin(HalError::new(&error))
+    }
+}
+
+impl Format for BusError {
+    fn format(&self, fmt: Formatter) {
+        match self {
+            BusError::Transfer(e) => {
+                write!(fmt, "Failed to transfer data over the data bus: {}", e)
+            }
+            BusError::ControlPin(e) => {
+                write!(fmt, "Failed to set or read a GPIO control pin: {}", e)
+            }
+        }
+    }
+}
+
+/// The maximum number of bytes of a HAL error's `Debug` output kept in a [`HalError`].
+pub const MAX_HAL_ERROR_LENGTH: usize = 64;
+
+/// An error returned by the HAL driving the data bus or a control pin. Every HAL has its
+/// own error types, so the error is kept as its `Debug` output, truncated to
+/// [`MAX_HAL_ERROR_LENGTH`] bytes, rather than making [`Error`] generic over all of them.
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct HalError(String<MAX_HAL_ERROR_LENGTH>);
+
+impl HalError {
+    /// Capture the `Debug` output of `error`.
+    pub fn new<E: Debug>(error: &E) -> Self {
+        let mut hal_error = HalError::default();
+        // Running out of room only cuts the output short, which is all that's wanted
+        fmt::write(
+            &mut TruncatingWriter(&mut hal_error.0),
+            format_args!("{:?}", error),
+        )
+        .ok();
+        hal_error
+    }
+
+    /// The `Debug` output of the HAL error.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl Format for HalError {
+    fn format(&self, fmt: Formatter) {
+        write!(fmt, "{=str}", self.as_str())
+    }
+}
+
+// Writes as much as fits into a String, one char at a time, so that no partial
+// UTF-8 sequence is ever written
+struct TruncatingWriter<'a, const N: usize>(&'a mut String<N>);
+
+impl<const N: usize> fmt::Write for TruncatingWriter<'_, N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            self.0.push(c).map_err(|_| fmt::Error)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<BusError> for Error {
+    fn from(err: BusError) -> Self {
+        Error::Bus(err)
+    }
+}
+
 impl From<protocol::ProtocolError> for Error {
     fn from(err: protocol::ProtocolError) -> Self {
         Error::Protocol(err)
@@ -255,4 +378,17 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn hal_error_new_keeps_the_debug_output_of_the_error() {
+        assert_eq!(HalError::new(&Some(42)).as_str(), "Some(42)");
+    }
+
+    #[test]
+    fn hal_error_new_truncates_debug_output_longer_than_max_hal_error_length() {
+        let hal_error = HalError::new(&[0u8; MAX_HAL_ERROR_LENGTH]);
+
+        assert_eq!(hal_error.as_str().len(), MAX_HAL_ERROR_LENGTH);
+        assert!(hal_error.as_str().starts_with("[0, 0, "));
+    }
 }
@@ -3,9 +3,47 @@
 
 use defmt::{write, Format, Formatter};
 
+use super::wifi::DisconnectReason;
+
 /// A four byte array type alias representing an IP address.
 pub type IpAddress = [u8; 4];
 
+/// A six byte MAC address, most significant byte first.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct MacAddress(pub [u8; 6]);
+
+impl Format for MacAddress {
+    fn format(&self, fmt: Formatter) {
+        let [a, b, c, d, e, f] = self.0;
+        write!(
+            fmt,
+            "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+            a, b, c, d, e, f
+        );
+    }
+}
+
+/// The IP configuration of the ESP32 target's network interface.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct IpConfig {
+    /// IP address of the network interface
+    pub ip: IpAddress,
+    /// Subnet mask of the network the interface is connected to
+    pub netmask: IpAddress,
+    /// IP address of the network's gateway
+    pub gateway: IpAddress,
+}
+
+impl Format for IpConfig {
+    fn format(&self, fmt: Formatter) {
+        write!(
+            fmt,
+            "IP: {:?}, Netmask: {:?}, Gateway: {:?}",
+            self.ip, self.netmask, self.gateway
+        );
+    }
+}
+
 /// A named string slice type representing a network hostname.
 pub type Hostname<'a> = &'a str;
 
@@ -23,14 +61,26 @@ pub enum TransportMode {
     Tcp = 0,
     /// UDP mode
     Udp = 1,
-    /// TLS mode
+    /// TLS mode. When connecting by [`Hostname`], the ESP32 target resolves the hostname
+    /// itself and uses it to verify the server's certificate.
     Tls = 2,
     /// UDP multicast mode
     UdpMulticast = 3,
-    /// TLS BearSSL mode
+    /// TLS BearSSL mode. Like [`TransportMode::Tls`], but uses the BearSSL TLS stack on the
+    /// ESP32 target, which also presents the client certificate and private key set with
+    /// `Wifi::set_client_certificate()` and `Wifi::set_private_key()` for mutual TLS. Opening
+    /// a connection in this mode fails with [`NetworkError::ClientCredentialsNotSet`] until
+    /// both have been set.
     TlsBearSsl = 4,
 }
 
+impl TransportMode {
+    // Does this mode establish a TLS session on top of TCP?
+    pub(crate) fn is_tls(&self) -> bool {
+        matches!(self, TransportMode::Tls | TransportMode::TlsBearSsl)
+    }
+}
+
 /// Defines all possible TCP connection states for a client or server instance.
 #[repr(u8)]
 #[derive(PartialEq, PartialOrd, Debug)]
@@ -106,8 +156,43 @@ pub enum NetworkError {
     ConnectionTimeout,
     /// Failed to connect to remote TCP server.
     ConnectFailed,
+    /// Failed to establish a TLS session with the remote server, e.g. because its
+    /// certificate couldn't be verified.
+    TlsHandshakeFailed,
     /// Failed to disconnect from remote TCP server.
     DisconnectFailed,
+    /// Failed to start a scan for nearby WiFi networks.
+    ScanFailed,
+    /// Failed to start an access point.
+    AccessPointFailed,
+    /// Failed to start listening on a local port.
+    BindFailed,
+    /// Failed to send data to a remote host.
+    SendFailed,
+    /// The SSID or credentials given to join a WiFi network have an invalid length or
+    /// key index.
+    InvalidCredentials,
+    /// Timed out while waiting to join a WiFi network.
+    JoinTimeout,
+    /// Failed to join a WiFi network for the given [`DisconnectReason`].
+    JoinFailed(DisconnectReason),
+    /// Timed out while waiting for a ping reply.
+    PingTimeout,
+    /// The remote host couldn't be reached.
+    HostUnreachable,
+    /// Failed to send a ping.
+    PingFailed,
+    /// The ESP32 target hasn't synced network time yet.
+    TimeNotSynced,
+    /// The remote host closed the connection.
+    ConnectionClosed,
+    /// The ESP32 target doesn't support the requested operation, e.g. IPv6.
+    Unsupported,
+    /// A [`TransportMode::TlsBearSsl`] connection was opened before both a client certificate
+    /// and a private key were set for mutual TLS.
+    ClientCredentialsNotSet,
+    /// The ESP32 target has no free socket left to open another connection with.
+    NoSocketAvailable,
 }
 
 impl Format for NetworkError {
@@ -125,9 +210,63 @@ impl Format for NetworkError {
             NetworkError::ConnectFailed => {
                 write!(fmt, "Failed to connect to remote TCP server")
             }
+            NetworkError::TlsHandshakeFailed => {
+                write!(fmt, "Failed to establish a TLS session with remote server")
+            }
             NetworkError::DisconnectFailed => {
                 write!(fmt, "Failed to start up a new TCP/UDP client instance")
             }
+            NetworkError::ScanFailed => {
+                write!(fmt, "Failed to start a scan for nearby WiFi networks")
+            }
+            NetworkError::AccessPointFailed => {
+                write!(fmt, "Failed to start an access point")
+            }
+            NetworkError::BindFailed => {
+                write!(fmt, "Failed to start listening on a local port")
+            }
+            NetworkError::SendFailed => {
+                write!(fmt, "Failed to send data to a remote host")
+            }
+            NetworkError::InvalidCredentials => {
+                write!(
+                    fmt,
+                    "Invalid SSID or credentials for joining a WiFi network"
+                )
+            }
+            NetworkError::JoinTimeout => {
+                write!(fmt, "Timed out while waiting to join a WiFi network")
+            }
+            NetworkError::JoinFailed(reason) => {
+                write!(fmt, "Failed to join a WiFi network: {}", reason)
+            }
+            NetworkError::PingTimeout => {
+                write!(fmt, "Timed out while waiting for a ping reply")
+            }
+            NetworkError::HostUnreachable => {
+                write!(fmt, "The remote host couldn't be reached")
+            }
+            NetworkError::PingFailed => {
+                write!(fmt, "Failed to send a ping")
+            }
+            NetworkError::TimeNotSynced => {
+                write!(fmt, "Network time hasn't been synced yet")
+            }
+            NetworkError::ConnectionClosed => {
+                write!(fmt, "The remote host closed the connection")
+            }
+            NetworkError::Unsupported => {
+                write!(fmt, "The operation isn't supported by the ESP32 target")
+            }
+            NetworkError::ClientCredentialsNotSet => {
+                write!(
+                    fmt,
+                    "A client certificate and private key must be set for mutual TLS"
+                )
+            }
+            NetworkError::NoSocketAvailable => {
+                write!(fmt, "The ESP32 target has no free socket left")
+            }
         }
     }
 }
@@ -0,0 +1,252 @@
+//! Use the ESP32 target as an [embedded-nal](https://docs.rs/embedded-nal) network stack,
+//! e.g. for MQTT, CoAP or HTTP client crates that are generic over it.
+//!
+//! ## Usage
+//!
+//! ```no_run
+//! use embedded_nal::{nb::block, Dns, TcpClientStack};
+//!
+//! let mut stack = NetworkStack::build(&mut wifi);
+//!
+//! let ip = block!(stack.get_host_by_name("example.com", AddrType::IPv4))?;
+//! let mut socket = stack.socket()?;
+//! block!(stack.connect(&mut socket, SocketAddr::new(ip, 80)))?;
+//!
+//! block!(stack.send(&mut socket, b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n"))?;
+//!
+//! let mut response = [0u8; 512];
+//! let length = block!(stack.receive(&mut socket, &mut response))?;
+//! defmt::info!("Response: {:?}", &response[..length]);
+//!
+//! stack.close(socket)?;
+//! ```
+//!
+
+use core::fmt::Debug;
+use core::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use embedded_hal::blocking::spi::Transfer;
+
+use embedded_nal::{nb, AddrType, Dns, TcpClientStack, UdpClientStack};
+
+use super::gpio::EspControlInterface;
+use super::network::{ConnectionState, IpAddress, NetworkError, Port, Socket, TransportMode};
+use super::protocol::{
+    NinaProtocolHandler, ProtocolInterface, MAX_NINA_LARGE_ARRAY_PARAM_BUFFER_LENGTH,
+};
+use super::wifi::Wifi;
+use super::Error;
+
+/// A TCP socket of a [`NetworkStack`], wrapping a socket number assigned by the ESP32 target.
+#[derive(Debug)]
+pub struct TcpSocketHandle {
+    pub(crate) socket: Socket,
+    pub(crate) connecting: bool,
+}
+
+/// A UDP socket of a [`NetworkStack`], wrapping a socket number assigned by the ESP32 target.
+#[derive(Debug)]
+pub struct UdpSocketHandle {
+    pub(crate) socket: Socket,
+    pub(crate) remote: Option<(IpAddress, Port)>,
+}
+
+/// An embedded-nal network stack provided a [`Wifi`] instance. Only IPv4 is supported.
+pub struct NetworkStack<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    pub(crate) protocol_handler: &'a mut NinaProtocolHandler<B, C>,
+}
+
+impl<'a, B, C> NetworkStack<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    /// Build a new instance of a [`NetworkStack`] provided a [`Wifi`] instance.
+    pub fn build(wifi: &'a mut Wifi<B, C>) -> Self {
+        Self {
+            protocol_handler: wifi.protocol_handler.get_mut(),
+        }
+    }
+}
+
+impl<'a, B, C> TcpClientStack for NetworkStack<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    type TcpSocket = TcpSocketHandle;
+    type Error = Error;
+
+    fn socket(&mut self) -> Result<TcpSocketHandle, Error> {
+        Ok(TcpSocketHandle {
+            socket: self.protocol_handler.get_socket()?,
+            connecting: false,
+        })
+    }
+
+    fn connect(
+        &mut self,
+        socket: &mut TcpSocketHandle,
+        remote: SocketAddr,
+    ) -> nb::Result<(), Error> {
+        if !socket.connecting {
+            let (ip, port) = ipv4_address(remote)?;
+            self.protocol_handler
+                .start_client_tcp(socket.socket, ip, port, &TransportMode::Tcp)?;
+            socket.connecting = true;
+        }
+
+        match self.protocol_handler.get_client_state_tcp(socket.socket)? {
+            ConnectionState::Established => {
+                socket.connecting = false;
+                Ok(())
+            }
+            ConnectionState::Closed => {
+                socket.connecting = false;
+                Err(nb::Error::Other(NetworkError::ConnectFailed.into()))
+            }
+            _ => Err(nb::Error::WouldBlock),
+        }
+    }
+
+    fn send(&mut self, socket: &mut TcpSocketHandle, buffer: &[u8]) -> nb::Result<usize, Error> {
+        // Anything beyond what fits into a single NINA command is left for the next call
+        let length = buffer.len().min(MAX_NINA_LARGE_ARRAY_PARAM_BUFFER_LENGTH);
+        match self
+            .protocol_handler
+            .send_data_with_pins_delay(&buffer[..length], socket.socket)?
+        {
+            0 if length > 0 => Err(nb::Error::WouldBlock),
+            written => Ok(written),
+        }
+    }
+
+    fn receive(
+        &mut self,
+        socket: &mut TcpSocketHandle,
+        buffer: &mut [u8],
+    ) -> nb::Result<usize, Error> {
+        let available = self.protocol_handler.avail_data_tcp(socket.socket)?;
+        if available > 0 {
+            let length = buffer.len().min(available);
+            // An empty read isn't the end of the connection, the data just isn't ready yet
+            return match self
+                .protocol_handler
+                .get_databuf_tcp(socket.socket, &mut buffer[..length])?
+            {
+                0 if length > 0 => Err(nb::Error::WouldBlock),
+                bytes_read => Ok(bytes_read),
+            };
+        }
+
+        match self.protocol_handler.get_client_state_tcp(socket.socket)? {
+            ConnectionState::Established => Err(nb::Error::WouldBlock),
+            _ => Err(nb::Error::Other(NetworkError::ConnectionClosed.into())),
+        }
+    }
+
+    fn close(&mut self, socket: TcpSocketHandle) -> Result<(), Error> {
+        self.protocol_handler
+            .stop_client_tcp(socket.socket, &TransportMode::Tcp)
+    }
+}
+
+impl<'a, B, C> UdpClientStack for NetworkStack<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    type UdpSocket = UdpSocketHandle;
+    type Error = Error;
+
+    fn socket(&mut self) -> Result<UdpSocketHandle, Error> {
+        Ok(UdpSocketHandle {
+            socket: self.protocol_handler.get_socket()?,
+            remote: None,
+        })
+    }
+
+    fn connect(&mut self, socket: &mut UdpSocketHandle, remote: SocketAddr) -> Result<(), Error> {
+        socket.remote = Some(ipv4_address(remote)?);
+        Ok(())
+    }
+
+    fn send(&mut self, socket: &mut UdpSocketHandle, buffer: &[u8]) -> nb::Result<(), Error> {
+        // Datagrams can only be sent after connect() has set the remote host
+        let (ip, port) = socket
+            .remote
+            .ok_or(nb::Error::Other(NetworkError::SendFailed.into()))?;
+        Ok(self
+            .protocol_handler
+            .send_datagram(socket.socket, ip, port, buffer)?)
+    }
+
+    fn receive(
+        &mut self,
+        socket: &mut UdpSocketHandle,
+        buffer: &mut [u8],
+    ) -> nb::Result<(usize, SocketAddr), Error> {
+        match self
+            .protocol_handler
+            .receive_datagram(socket.socket, buffer)?
+        {
+            Some((bytes_read, ip, port)) => {
+                Ok((bytes_read, SocketAddr::new(Ipv4Addr::from(ip).into(), port)))
+            }
+            None => Err(nb::Error::WouldBlock),
+        }
+    }
+
+    fn close(&mut self, socket: UdpSocketHandle) -> Result<(), Error> {
+        self.protocol_handler
+            .stop_client_tcp(socket.socket, &TransportMode::Udp)
+    }
+}
+
+impl<'a, B, C> Dns for NetworkStack<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    type Error = Error;
+
+    fn get_host_by_name(
+        &mut self,
+        hostname: &str,
+        addr_type: AddrType,
+    ) -> nb::Result<IpAddr, Error> {
+        if addr_type == AddrType::IPv6 {
+            return Err(nb::Error::Other(NetworkError::Unsupported.into()));
+        }
+
+        let ip = self.protocol_handler.resolve(hostname)?;
+
+        Ok(Ipv4Addr::from(ip).into())
+    }
+
+    fn get_host_by_address(
+        &mut self,
+        _addr: IpAddr,
+        _result: &mut [u8],
+    ) -> nb::Result<usize, Error> {
+        // NINA firmware can't do reverse DNS lookups
+        Err(nb::Error::Other(NetworkError::Unsupported.into()))
+    }
+}
+
+// Splits an IPv4 socket address into the IP address and port NINA firmware expects.
+fn ipv4_address(address: SocketAddr) -> Result<(IpAddress, Port), Error> {
+    match address {
+        SocketAddr::V4(address) => Ok((address.ip().octets(), address.port())),
+        SocketAddr::V6(_) => Err(NetworkError::Unsupported.into()),
+    }
+}
@@ -2,8 +2,10 @@
 //!
 
 pub(crate) mod operation;
+pub(crate) mod request;
 
 use core::cell::RefCell;
+use core::ops::Index;
 
 use defmt::{write, Format, Formatter};
 
@@ -11,12 +13,18 @@ use embedded_hal::blocking::delay::DelayMs;
 
 use heapless::{String, Vec};
 
-use super::network::{ConnectionState, IpAddress, Port, Socket, TransportMode};
-use super::wifi::ConnectionStatus;
+use super::network::{
+    ConnectionState, IpAddress, IpConfig, MacAddress, NetworkError, Port, Socket, TransportMode,
+};
+use super::wifi::{
+    ConnectionStatus, DisconnectReason, EncryptionType, Rssi, ScanResult, MAX_SCAN_RESULTS,
+    MAX_SSID_LENGTH,
+};
 use super::{Error, FirmwareVersion};
 
-// The maximum number of NINA param u8 bytes in a command send/receive byte stream
-pub(crate) const MAX_NINA_PARAMS: usize = 8;
+// The maximum number of params NINA firmware returns in a single response
+// (e.g. one SSID per network found by a WiFi scan)
+pub(crate) const MAX_NINA_PARAMS: usize = 10;
 
 pub(crate) const MAX_NINA_BYTE_PARAM_BUFFER_LENGTH: usize = 1;
 pub(crate) const MAX_NINA_WORD_PARAM_BUFFER_LENGTH: usize = 2;
@@ -26,25 +34,311 @@ pub(crate) const MAX_NINA_LARGE_ARRAY_PARAM_BUFFER_LENGTH: usize = 1024;
 // The maximum length that a 2-byte length NINA response can be
 pub(crate) const MAX_NINA_RESPONSE_LENGTH: usize = 1024;
 
-// TODO: unalias this type and turn into a full wrapper struct
-/// Provides a byte buffer to hold responses returned from NINA-FW
-pub type NinaResponseBuffer = [u8; MAX_NINA_RESPONSE_LENGTH];
+// The number of bytes read while waiting for the start of a response before giving up
+const MAX_START_BYTE_WAIT: u16 = 1000;
+
+#[repr(u8)]
+#[derive(Debug)]
+pub(crate) enum ControlByte {
+    Start = 0xE0u8,
+    End = 0xEEu8,
+    Reply = 1u8 << 7u8,
+    Dummy = 0xFFu8,
+    Error = 0xEFu8,
+}
 
 #[repr(u8)]
 #[derive(Copy, Clone, Debug)]
 pub(crate) enum NinaCommand {
+    SetNet = 0x10u8,
     SetPassphrase = 0x11u8,
+    SetKey = 0x12u8,
+    SetIPConfig = 0x14u8,
     SetDNSConfig = 0x15u8,
+    SetHostname = 0x16u8,
+    SetApNet = 0x18u8,
+    SetApPassphrase = 0x19u8,
+    GetReasonCode = 0x1fu8,
     GetConnStatus = 0x20u8,
+    GetIPAddr = 0x21u8,
+    GetMacAddr = 0x22u8,
+    GetCurrSsid = 0x23u8,
+    GetCurrBssid = 0x24u8,
+    GetCurrRssi = 0x25u8,
+    GetCurrEnct = 0x26u8,
+    ScanNetworks = 0x27u8,
+    StartServerTcp = 0x28u8,
+    GetStateTcp = 0x29u8,
+    DataSentTcp = 0x2au8,
+    AvailDataTcp = 0x2bu8,
+    GetDataTcp = 0x2cu8,
     StartClientTcp = 0x2du8,
     StopClientTcp = 0x2eu8,
     GetClientStateTcp = 0x2fu8,
     Disconnect = 0x30u8,
+    GetIdxRssi = 0x32u8,
+    GetIdxEnct = 0x33u8,
     ReqHostByName = 0x34u8,
     GetHostByName = 0x35u8,
+    StartScanNetworks = 0x36u8,
     GetFwVersion = 0x37u8,
+    SendDataUdp = 0x39u8,
+    GetRemoteData = 0x3au8,
+    GetTime = 0x3bu8,
+    GetIdxBssid = 0x3cu8,
+    GetIdxChannel = 0x3du8,
+    Ping = 0x3eu8,
     GetSocket = 0x3fu8,
     SendDataTcp = 0x44,
+    SetClientCert = 0x40,
+    SetPrivateKey = 0x41,
+    GetDatabufTcp = 0x45,
+    InsertDatabuf = 0x46,
+    SetEntIdent = 0x4a,
+    SetEntUname = 0x4b,
+    SetEntPasswd = 0x4c,
+    SetEntCaCert = 0x4d,
+    SetEntEnable = 0x4f,
+}
+
+impl NinaCommand {
+    // The number of bytes used to represent the length of each param in this
+    // command's response
+    pub(crate) fn response_param_length_size(&self) -> u8 {
+        match self {
+            NinaCommand::GetDatabufTcp => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// A response returned from NINA-FW that holds zero or more params, each
+/// accessible as a byte slice.
+#[derive(Debug)]
+pub struct NinaResponse {
+    buffer: [u8; MAX_NINA_RESPONSE_LENGTH],
+    // The (offset, length) of each param within buffer
+    params: Vec<(usize, usize), MAX_NINA_PARAMS>,
+}
+
+impl NinaResponse {
+    // Reads the response to `command` one byte at a time from `read_byte`, checking it holds
+    // `expected_num_params` params unless any number of them is expected.
+    pub(crate) fn read<F>(
+        command: &NinaCommand,
+        expected_num_params: Option<u8>,
+        mut read_byte: F,
+    ) -> Result<Self, Error>
+    where
+        F: FnMut() -> Result<u8, Error>,
+    {
+        let mut reader = NinaResponseReader::new(command, expected_num_params);
+        while !reader.push(read_byte()?)? {}
+
+        Ok(reader.finish())
+    }
+
+    /// The number of params contained in the response.
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    /// Returns true if the response contains no params.
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Get the param at `index`, or `None` if the response has no such param.
+    pub fn param(&self, index: usize) -> Option<&[u8]> {
+        self.params
+            .get(index)
+            .map(|(offset, length)| &self.buffer[*offset..*offset + *length])
+    }
+
+    /// Iterate over all params contained in the response.
+    pub fn params(&self) -> impl Iterator<Item = &[u8]> {
+        self.params
+            .iter()
+            .map(|(offset, length)| &self.buffer[*offset..*offset + *length])
+    }
+
+    // Get the first N bytes of the param at `index`.
+    pub(crate) fn param_as_array<const N: usize>(&self, index: usize) -> Result<[u8; N], Error> {
+        let param = self
+            .param(index)
+            .ok_or(ProtocolError::InvalidNumberOfParameters)?;
+        if param.len() < N {
+            return Err(ProtocolError::InvalidParamLength.into());
+        }
+
+        let mut bytes: [u8; N] = [0; N];
+        bytes.copy_from_slice(&param[..N]);
+        Ok(bytes)
+    }
+
+    // Get the first byte of the param at `index`.
+    pub(crate) fn param_as_u8(&self, index: usize) -> Result<u8, Error> {
+        Ok(self.param_as_array::<1>(index)?[0])
+    }
+}
+
+// Where a NinaResponseReader is within the response
+#[derive(Clone, Copy, Debug)]
+enum ReaderState {
+    Start { bytes_skipped: u16 },
+    Error { remaining: u8 },
+    Command,
+    NumberOfParams,
+    Length { bytes_read: u8, length: usize },
+    Data { remaining: usize },
+    End,
+}
+
+// Builds up a NinaResponse as its bytes are fed in one at a time, so that the blocking
+// and async protocol handlers can share parsing while reading from the bus their own way.
+pub(crate) struct NinaResponseReader {
+    response: NinaResponse,
+    command: NinaCommand,
+    expected_num_params: Option<u8>,
+    number_of_params: u8,
+    offset: usize,
+    state: ReaderState,
+}
+
+impl NinaResponseReader {
+    // Prepares to read the response to `command`: the start byte, the reply to the command,
+    // the number of params (checked against `expected_num_params` unless it's None), each
+    // param prefixed with its big-endian length and finally the end byte.
+    pub(crate) fn new(command: &NinaCommand, expected_num_params: Option<u8>) -> Self {
+        NinaResponseReader {
+            response: NinaResponse {
+                buffer: [0; MAX_NINA_RESPONSE_LENGTH],
+                params: Vec::new(),
+            },
+            command: *command,
+            expected_num_params,
+            number_of_params: 0,
+            offset: 0,
+            state: ReaderState::Start { bytes_skipped: 0 },
+        }
+    }
+
+    // Feeds in the next byte of the response, returning true once the end byte was read.
+    pub(crate) fn push(&mut self, byte: u8) -> Result<bool, Error> {
+        match self.state {
+            ReaderState::Start { bytes_skipped } => {
+                if byte == ControlByte::Start as u8 {
+                    self.state = ReaderState::Command;
+                } else if byte == ControlByte::Error as u8 {
+                    // consume remaining bytes after error: 0x00, 0xEE
+                    self.state = ReaderState::Error { remaining: 2 };
+                } else if bytes_skipped + 1 < MAX_START_BYTE_WAIT {
+                    self.state = ReaderState::Start {
+                        bytes_skipped: bytes_skipped + 1,
+                    };
+                } else {
+                    return Err(ProtocolError::CommunicationTimeout.into());
+                }
+            }
+            ReaderState::Error { remaining } => {
+                if remaining <= 1 {
+                    return Err(ProtocolError::NinaProtocolVersionMismatch.into());
+                }
+                self.state = ReaderState::Error {
+                    remaining: remaining - 1,
+                };
+            }
+            ReaderState::Command => {
+                // Ensure we see a cmd byte
+                if byte != self.command as u8 | ControlByte::Reply as u8 {
+                    return Err(ProtocolError::InvalidCommand.into());
+                }
+                self.state = ReaderState::NumberOfParams;
+            }
+            ReaderState::NumberOfParams => {
+                if byte as usize > MAX_NINA_PARAMS {
+                    return Err(ProtocolError::TooManyParameters.into());
+                }
+
+                // Ensure we see the number of params we expected to receive back
+                if let Some(expected_num_params) = self.expected_num_params {
+                    if byte != expected_num_params {
+                        return Err(ProtocolError::InvalidNumberOfParameters.into());
+                    }
+                }
+
+                self.number_of_params = byte;
+                self.next_param();
+            }
+            ReaderState::Length { bytes_read, length } => {
+                let length = (length << 8) | byte as usize;
+                if bytes_read + 1 < self.command.response_param_length_size() {
+                    self.state = ReaderState::Length {
+                        bytes_read: bytes_read + 1,
+                        length,
+                    };
+                    return Ok(false);
+                }
+
+                if self.offset + length > MAX_NINA_RESPONSE_LENGTH {
+                    return Err(ProtocolError::PayloadTooLarge.into());
+                }
+
+                // Can't overflow since number_of_params was checked against MAX_NINA_PARAMS
+                self.response.params.push((self.offset, length)).ok();
+                if length > 0 {
+                    self.state = ReaderState::Data { remaining: length };
+                } else {
+                    self.next_param();
+                }
+            }
+            ReaderState::Data { remaining } => {
+                self.response.buffer[self.offset] = byte;
+                self.offset += 1;
+                if remaining > 1 {
+                    self.state = ReaderState::Data {
+                        remaining: remaining - 1,
+                    };
+                } else {
+                    self.next_param();
+                }
+            }
+            ReaderState::End => {
+                if byte != ControlByte::End as u8 {
+                    return Err(ProtocolError::MissingEndByte.into());
+                }
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    // Returns the response once push() has reported it complete.
+    pub(crate) fn finish(self) -> NinaResponse {
+        self.response
+    }
+
+    // Moves on to the length of the next param, or to the end byte after the last one.
+    fn next_param(&mut self) {
+        self.state = if self.response.params.len() < self.number_of_params as usize {
+            ReaderState::Length {
+                bytes_read: 0,
+                length: 0,
+            }
+        } else {
+            ReaderState::End
+        };
+    }
+}
+
+impl Index<usize> for NinaResponse {
+    type Output = [u8];
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.param(index)
+            .expect("NINA response param index out of range")
+    }
 }
 
 pub(crate) trait NinaConcreteParam
@@ -114,6 +408,41 @@ pub(crate) struct NinaAbstractParam {
     length_size: u8,
 }
 
+// Used for 2-byte length params that borrow their data rather than copying it into a
+// fixed size buffer, so they can be larger than MAX_NINA_LARGE_ARRAY_PARAM_BUFFER_LENGTH
+#[derive(PartialEq, Debug)]
+pub(crate) struct NinaBorrowedParam<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> NinaBorrowedParam<'a> {
+    pub(crate) fn from_bytes(bytes: &'a [u8]) -> Result<Self, Error> {
+        if bytes.len() > u16::MAX as usize {
+            return Err(ProtocolError::PayloadTooLarge.into());
+        }
+
+        Ok(Self { data: bytes })
+    }
+}
+
+impl NinaParam for NinaBorrowedParam<'_> {
+    fn length_as_bytes(&self) -> [u8; 2] {
+        (self.data.len() as u16).to_be_bytes()
+    }
+
+    fn data(&self) -> &[u8] {
+        self.data
+    }
+
+    fn length(&self) -> u16 {
+        self.data.len() as u16
+    }
+
+    fn length_size(&self) -> u8 {
+        2
+    }
+}
+
 impl NinaParam for NinaAbstractParam {
     fn length_as_bytes(&self) -> [u8; 2] {
         self.length_as_bytes
@@ -384,17 +713,45 @@ impl Default for NinaLargeArrayParam {
 }
 
 pub(crate) trait ProtocolInterface {
-    fn init(&mut self);
-    fn reset<D: DelayMs<u16>>(&mut self, delay: &mut D);
+    fn init(&mut self) -> Result<(), Error>;
+    fn reset<D: DelayMs<u16>>(&mut self, delay: &mut D) -> Result<(), Error>;
     fn get_fw_version(&mut self) -> Result<FirmwareVersion, Error>;
+    fn set_network(&mut self, ssid: &str) -> Result<(), Error>;
     fn set_passphrase(&mut self, ssid: &str, passphrase: &str) -> Result<(), Error>;
+    fn set_key(&mut self, ssid: &str, index: u8, key: &str) -> Result<(), Error>;
+    fn set_ent_identity(&mut self, identity: &str) -> Result<(), Error>;
+    fn set_ent_username(&mut self, username: &str) -> Result<(), Error>;
+    fn set_ent_password(&mut self, password: &str) -> Result<(), Error>;
+    fn set_ent_ca_cert(&mut self, ca_cert: &[u8]) -> Result<(), Error>;
+    fn set_ent_enable(&mut self) -> Result<(), Error>;
+    fn set_ap_net(&mut self, ssid: &str, channel: u8) -> Result<(), Error>;
+    fn set_ap_passphrase(&mut self, ssid: &str, passphrase: &str, channel: u8)
+        -> Result<(), Error>;
+    fn set_client_cert(&mut self, certificate: &[u8]) -> Result<(), Error>;
+    fn set_private_key(&mut self, private_key: &[u8]) -> Result<(), Error>;
     fn disconnect(&mut self) -> Result<(), Error>;
     fn get_conn_status(&mut self) -> Result<ConnectionStatus, Error>;
+    fn get_reason_code(&mut self) -> Result<DisconnectReason, Error>;
     fn set_dns_config(&mut self, dns1: IpAddress, dns2: Option<IpAddress>) -> Result<(), Error>;
+    fn set_ip_config(
+        &mut self,
+        local_ip: IpAddress,
+        gateway: IpAddress,
+        subnet: IpAddress,
+    ) -> Result<(), Error>;
+    fn get_ip_addr(&mut self) -> Result<IpConfig, Error>;
+    fn get_mac_addr(&mut self) -> Result<MacAddress, Error>;
+    fn get_curr_ssid(&mut self) -> Result<String<MAX_SSID_LENGTH>, Error>;
+    fn get_curr_bssid(&mut self) -> Result<MacAddress, Error>;
+    fn get_curr_rssi(&mut self) -> Result<Rssi, Error>;
+    fn get_curr_enct(&mut self) -> Result<EncryptionType, Error>;
     fn req_host_by_name(&mut self, hostname: &str) -> Result<u8, Error>;
-    fn get_host_by_name(&mut self) -> Result<[u8; MAX_NINA_RESPONSE_LENGTH], Error>;
+    fn get_host_by_name(&mut self) -> Result<IpAddress, Error>;
     fn resolve(&mut self, hostname: &str) -> Result<IpAddress, Error>;
     fn get_socket(&mut self) -> Result<Socket, Error>;
+    fn ping(&mut self, ip: IpAddress, ttl: u8) -> Result<u16, Error>;
+    fn get_time(&mut self) -> Result<u32, Error>;
+    fn set_hostname(&mut self, hostname: &str) -> Result<(), Error>;
     fn start_client_tcp(
         &mut self,
         socket: Socket,
@@ -402,9 +759,57 @@ pub(crate) trait ProtocolInterface {
         port: Port,
         mode: &TransportMode,
     ) -> Result<(), Error>;
+    fn start_client_tcp_hostname(
+        &mut self,
+        socket: Socket,
+        hostname: &str,
+        port: Port,
+        mode: &TransportMode,
+    ) -> Result<(), Error>;
     fn stop_client_tcp(&mut self, socket: Socket, _mode: &TransportMode) -> Result<(), Error>;
     fn get_client_state_tcp(&mut self, socket: Socket) -> Result<ConnectionState, Error>;
-    fn send_data(&mut self, data: &str, socket: Socket) -> Result<[u8; 1], Error>;
+    fn start_server_tcp(
+        &mut self,
+        socket: Socket,
+        port: Port,
+        mode: &TransportMode,
+    ) -> Result<(), Error>;
+    fn get_server_state_tcp(&mut self, socket: Socket) -> Result<ConnectionState, Error>;
+    fn send_data<D: DelayMs<u16>>(
+        &mut self,
+        data: &[u8],
+        socket: Socket,
+        delay: &mut D,
+    ) -> Result<usize, Error>;
+    fn data_sent_tcp(&mut self, socket: Socket) -> Result<bool, Error>;
+    fn avail_data_tcp(&mut self, socket: Socket) -> Result<usize, Error>;
+    fn get_data_tcp(&mut self, socket: Socket, peek: bool) -> Result<u8, Error>;
+    fn get_databuf_tcp(&mut self, socket: Socket, data: &mut [u8]) -> Result<usize, Error>;
+    fn receive_data(&mut self, socket: Socket, data: &mut [u8]) -> Result<usize, Error>;
+    fn insert_databuf(&mut self, socket: Socket, data: &[u8]) -> Result<(), Error>;
+    fn send_data_udp(&mut self, socket: Socket) -> Result<(), Error>;
+    fn get_remote_data(&mut self, socket: Socket) -> Result<(IpAddress, Port), Error>;
+    fn send_datagram(
+        &mut self,
+        socket: Socket,
+        ip: IpAddress,
+        port: Port,
+        data: &[u8],
+    ) -> Result<(), Error>;
+    fn receive_datagram(
+        &mut self,
+        socket: Socket,
+        data: &mut [u8],
+    ) -> Result<Option<(usize, IpAddress, Port)>, Error>;
+    fn start_scan_networks(&mut self) -> Result<(), Error>;
+    fn get_scan_networks(
+        &mut self,
+    ) -> Result<Vec<String<MAX_SSID_LENGTH>, MAX_SCAN_RESULTS>, Error>;
+    fn get_idx_rssi(&mut self, index: u8) -> Result<Rssi, Error>;
+    fn get_idx_enct(&mut self, index: u8) -> Result<EncryptionType, Error>;
+    fn get_idx_bssid(&mut self, index: u8) -> Result<[u8; 6], Error>;
+    fn get_idx_channel(&mut self, index: u8) -> Result<u8, Error>;
+    fn scan_networks(&mut self) -> Result<Vec<ScanResult, MAX_SCAN_RESULTS>, Error>;
 }
 
 #[derive(Debug)]
@@ -413,6 +818,34 @@ pub(crate) struct NinaProtocolHandler<B, C> {
     pub bus: RefCell<B>,
     /// An EspControlPins instance
     pub control_pins: C,
+    /// Time in milliseconds to wait for the NINA firmware to become ready for each command
+    pub command_timeout_ms: u32,
+    /// Whether a client certificate and private key for mutual TLS have been stored on the
+    /// NINA firmware since it was last reset
+    pub client_credentials: ClientCredentials,
+}
+
+// NINA firmware only presents a client certificate once both it and its private key
+// have been set, so both are tracked to catch a half-configured mutual TLS connection.
+#[derive(Debug, Default)]
+pub(crate) struct ClientCredentials {
+    pub certificate: bool,
+    pub private_key: bool,
+}
+
+impl ClientCredentials {
+    pub(crate) fn is_set(&self) -> bool {
+        self.certificate && self.private_key
+    }
+
+    // Fails before anything is sent if a TLS BearSSL connection would be opened without
+    // the client certificate and private key it presents for mutual TLS.
+    pub(crate) fn check(&self, mode: &TransportMode) -> Result<(), Error> {
+        if *mode == TransportMode::TlsBearSsl && !self.is_set() {
+            return Err(NetworkError::ClientCredentialsNotSet.into());
+        }
+        Ok(())
+    }
 }
 
 // TODO: look at Nina Firmware code to understand conditions
@@ -434,6 +867,10 @@ pub enum ProtocolError {
     /// Payload is larger than the maximum buffer size allowed for transmission over
     /// the data bus.
     PayloadTooLarge,
+    /// A response param is shorter than expected for its NINA command.
+    InvalidParamLength,
+    /// A response did not finish with the expected end byte.
+    MissingEndByte,
 }
 
 impl Format for ProtocolError {
@@ -445,6 +882,8 @@ impl Format for ProtocolError {
             ProtocolError::InvalidNumberOfParameters => write!(fmt, "Encountered an unexpected number of parameters for a NINA command while communicating with ESP32 target."),
             ProtocolError::TooManyParameters => write!(fmt, "Encountered too many parameters for a NINA command while communicating with ESP32 target."),
             ProtocolError::PayloadTooLarge => write!(fmt, "The payload is larger than the max buffer size allowed for a NINA parameter while communicating with ESP32 target."),
+            ProtocolError::InvalidParamLength => write!(fmt, "Encountered a response parameter that is shorter than expected for a NINA command while communicating with ESP32 target."),
+            ProtocolError::MissingEndByte => write!(fmt, "Encountered a response without the expected end byte while communicating with ESP32 target."),
         }
     }
 }
@@ -545,4 +984,146 @@ mod protocol_tests {
             Error::Protocol(ProtocolError::PayloadTooLarge)
         )
     }
+
+    fn read_from(bytes: &[u8]) -> impl FnMut() -> Result<u8, Error> + '_ {
+        let mut bytes = bytes.iter();
+        move || Ok(*bytes.next().unwrap())
+    }
+
+    #[test]
+    fn nina_response_read_parses_multiple_params_with_single_byte_lengths() {
+        let bytes = [
+            0xe0, 0xa7, 0x2, 0x2, b'a', b'b', 0x3, b'c', b'd', b'e', 0xee,
+        ];
+        let response =
+            NinaResponse::read(&NinaCommand::ScanNetworks, None, read_from(&bytes)).unwrap();
+
+        assert_eq!(response.len(), 2);
+        assert_eq!(&response[0], b"ab");
+        assert_eq!(response.param(1), Some(&b"cde"[..]));
+        assert_eq!(response.param(2), None);
+        assert_eq!(
+            response.params().collect::<Vec<&[u8], 2>>().as_slice(),
+            &[&b"ab"[..], &b"cde"[..]]
+        );
+    }
+
+    #[test]
+    fn nina_response_read_parses_params_with_double_byte_lengths() {
+        let mut bytes = [0xAu8; 306];
+        bytes[..5].copy_from_slice(&[0xe0, 0xc5, 0x1, 0x1, 0x2c]);
+        bytes[305] = 0xee;
+        let response =
+            NinaResponse::read(&NinaCommand::GetDatabufTcp, Some(1), read_from(&bytes)).unwrap();
+
+        assert_eq!(response.len(), 1);
+        assert_eq!(&response[0], &[0xA; 300][..]);
+    }
+
+    #[test]
+    fn nina_response_read_parses_a_response_without_params() {
+        let bytes = [0xe0, 0xa7, 0x0, 0xee];
+        let response =
+            NinaResponse::read(&NinaCommand::ScanNetworks, None, read_from(&bytes)).unwrap();
+
+        assert!(response.is_empty());
+    }
+
+    #[test]
+    fn nina_response_read_returns_missing_end_byte_error_when_end_byte_is_wrong() {
+        let bytes = [0xe0, 0xa0, 0x1, 0x1, 0x1, 0x0];
+        let result = NinaResponse::read(&NinaCommand::GetConnStatus, Some(1), read_from(&bytes));
+
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Protocol(ProtocolError::MissingEndByte)
+        )
+    }
+
+    #[test]
+    fn nina_response_read_returns_too_many_parameters_error_when_given_too_many_params() {
+        let bytes = [0xe0, 0xa7, MAX_NINA_PARAMS as u8 + 1];
+        let result = NinaResponse::read(&NinaCommand::ScanNetworks, None, read_from(&bytes));
+
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Protocol(ProtocolError::TooManyParameters)
+        )
+    }
+
+    #[test]
+    fn nina_response_read_returns_payload_too_large_error_when_params_exceed_buffer() {
+        let bytes = [0xe0, 0xc5, 0x1, 0x4, 0x1];
+        let result = NinaResponse::read(&NinaCommand::GetDatabufTcp, Some(1), read_from(&bytes));
+
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Protocol(ProtocolError::PayloadTooLarge)
+        )
+    }
+
+    #[test]
+    fn nina_response_param_as_array_returns_invalid_param_length_error_when_param_is_too_short() {
+        let bytes = [0xe0, 0xa0, 0x1, 0x2, 0x1, 0x2, 0xee];
+        let response =
+            NinaResponse::read(&NinaCommand::GetConnStatus, Some(1), read_from(&bytes)).unwrap();
+
+        assert_eq!(
+            response.param_as_array::<4>(0).unwrap_err(),
+            Error::Protocol(ProtocolError::InvalidParamLength)
+        )
+    }
+
+    #[test]
+    fn nina_response_read_skips_bytes_before_the_start_byte() {
+        let bytes = [0xff, 0xff, 0xe0, 0xa0, 0x1, 0x1, 0x3, 0xee];
+        let response =
+            NinaResponse::read(&NinaCommand::GetConnStatus, Some(1), read_from(&bytes)).unwrap();
+
+        assert_eq!(response.param_as_u8(0).unwrap(), 0x3);
+    }
+
+    #[test]
+    fn nina_response_read_returns_communication_timeout_error_without_a_start_byte() {
+        let bytes = [0xffu8; MAX_START_BYTE_WAIT as usize];
+        let result = NinaResponse::read(&NinaCommand::GetConnStatus, Some(1), read_from(&bytes));
+
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Protocol(ProtocolError::CommunicationTimeout)
+        )
+    }
+
+    #[test]
+    fn nina_response_read_returns_protocol_version_mismatch_error_after_reading_error_reply() {
+        let bytes = [0xef, 0x0, 0xee];
+        let result = NinaResponse::read(&NinaCommand::GetConnStatus, Some(1), read_from(&bytes));
+
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Protocol(ProtocolError::NinaProtocolVersionMismatch)
+        )
+    }
+
+    #[test]
+    fn nina_response_read_returns_invalid_command_error_when_reply_is_for_another_command() {
+        let bytes = [0xe0, 0xa1, 0x1];
+        let result = NinaResponse::read(&NinaCommand::GetConnStatus, Some(1), read_from(&bytes));
+
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Protocol(ProtocolError::InvalidCommand)
+        )
+    }
+
+    #[test]
+    fn nina_response_read_returns_invalid_number_of_parameters_error_when_count_is_unexpected() {
+        let bytes = [0xe0, 0xa0, 0x2];
+        let result = NinaResponse::read(&NinaCommand::GetConnStatus, Some(1), read_from(&bytes));
+
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Protocol(ProtocolError::InvalidNumberOfParameters)
+        )
+    }
 }
@@ -1,6 +1,8 @@
+use core::iter;
+
 use heapless::Vec;
 
-use super::{NinaAbstractParam, NinaCommand};
+use super::{ControlByte, NinaAbstractParam, NinaBorrowedParam, NinaCommand, NinaParam};
 
 const MAX_NUMBER_OF_PARAMS: usize = 6;
 
@@ -30,3 +32,60 @@ impl Operation<NinaAbstractParam> {
         self
     }
 }
+
+impl<'a> Operation<NinaBorrowedParam<'a>> {
+    // Initializes a new Operation instance with a specified command whose params
+    // borrow their data instead of copying it.
+    pub fn new_borrowed(nina_command: NinaCommand) -> Self {
+        Self {
+            params: Vec::new(),
+            command: nina_command,
+        }
+    }
+
+    // Pushes a new borrowed param into the internal `params` Vector.
+    pub fn param(mut self, param: NinaBorrowedParam<'a>) -> Self {
+        // FIXME: Vec::push() will return T when it is full, handle this gracefully
+        self.params.push(param).unwrap_or(());
+        self
+    }
+}
+
+impl<P: NinaParam> Operation<P> {
+    // Encodes the whole command as it goes out on the data bus, shared by the blocking and
+    // async protocol handlers: start byte, command, number of params, each param prefixed
+    // with its length, end byte and finally padding up to a multiple of 4 bytes.
+    pub fn bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        let header = [
+            ControlByte::Start as u8,
+            (self.command as u8) & !(ControlByte::Reply as u8),
+            self.params.len() as u8,
+        ];
+
+        let params = self.params.iter().flat_map(|param| {
+            let length_size = param.length_size() as usize;
+            param
+                .length_as_bytes()
+                .into_iter()
+                .take(length_size)
+                .chain(param.data().iter().copied())
+        });
+
+        // 4 (start byte, command byte, number of params as byte, end byte)
+        // + the number of bytes to represent each param length (1 or 2)
+        // + the sum of all param lengths
+        // See https://github.com/arduino/nina-fw/blob/master/main/CommandHandler.cpp#L2153 for the actual equation.
+        let command_size: usize = 4 + self
+            .params
+            .iter()
+            .map(|param| param.length_size() as usize + param.length() as usize)
+            .sum::<usize>();
+        let padding = (4 - command_size % 4) % 4;
+
+        header
+            .into_iter()
+            .chain(params)
+            .chain(iter::once(ControlByte::End as u8))
+            .chain(iter::repeat_n(ControlByte::Dummy as u8, padding))
+    }
+}
@@ -0,0 +1,707 @@
+use core::{slice, str};
+
+use heapless::{String, Vec};
+
+use super::operation::Operation;
+use super::{
+    ControlByte, NinaAbstractParam, NinaBorrowedParam, NinaByteParam, NinaCommand,
+    NinaConcreteParam, NinaLargeArrayParam, NinaResponse, NinaSmallArrayParam, NinaWordParam,
+    ProtocolError, MAX_NINA_LARGE_ARRAY_PARAM_BUFFER_LENGTH, MAX_NINA_RESPONSE_LENGTH,
+};
+use crate::network::{
+    ConnectionState, IpAddress, IpConfig, MacAddress, NetworkError, Port, Socket, TransportMode,
+};
+use crate::wifi::{
+    ConnectionStatus, DisconnectReason, EncryptionType, Rssi, MAX_CLIENT_CERTIFICATE_LENGTH,
+    MAX_PRIVATE_KEY_LENGTH, MAX_SCAN_RESULTS, MAX_SSID_LENGTH,
+};
+use crate::{Error, FirmwareVersion};
+
+// NINA firmware replies to GET_SOCKET with this when all of its sockets are in use
+const NO_SOCKET_AVAILABLE: Socket = 255;
+
+// Negative round trip times NINA firmware reports for a failed ping
+const PING_DEST_UNREACHABLE: i16 = -1;
+const PING_TIMEOUT: i16 = -2;
+const PING_UNKNOWN_HOST: i16 = -3;
+
+// A command along with how to interpret the params NINA firmware responds to it with.
+// Both the blocking and async protocol handlers send these, so they only differ in how they
+// talk to the bus, not in what they send or how they read the result.
+pub(crate) struct Request<P, F> {
+    pub operation: Operation<P>,
+    // How many params the response holds, or None for any number of them
+    pub num_params: Option<u8>,
+    pub parse: F,
+}
+
+impl<P, F> Request<P, F> {
+    // Most responses hold a single param
+    fn new<T>(operation: Operation<P>, parse: F) -> Self
+    where
+        F: Parse<T>,
+    {
+        Self {
+            operation,
+            num_params: Some(1),
+            parse,
+        }
+    }
+
+    fn expecting(mut self, num_params: Option<u8>) -> Self {
+        self.num_params = num_params;
+        self
+    }
+}
+
+// Interprets the response to a request, e.g. into the current ConnectionStatus
+pub(crate) trait Parse<T>: FnOnce(&NinaResponse) -> Result<T, Error> {}
+
+impl<T, F> Parse<T> for F where F: FnOnce(&NinaResponse) -> Result<T, Error> {}
+
+pub(crate) fn get_fw_version() -> Request<NinaAbstractParam, impl Parse<FirmwareVersion>> {
+    Request::new(Operation::new(NinaCommand::GetFwVersion), |result| {
+        let version = result.param_as_array::<5>(0)?;
+
+        Ok(FirmwareVersion::new(&version)) // e.g. 1.7.4
+    })
+}
+
+pub(crate) fn set_network(ssid: &str) -> Result<Request<NinaAbstractParam, impl Parse<()>>, Error> {
+    let operation = Operation::new(NinaCommand::SetNet).param(NinaSmallArrayParam::new(ssid)?);
+
+    Ok(Request::new(operation, |_| Ok(())))
+}
+
+pub(crate) fn set_passphrase(
+    ssid: &str,
+    passphrase: &str,
+) -> Result<Request<NinaAbstractParam, impl Parse<()>>, Error> {
+    let operation = Operation::new(NinaCommand::SetPassphrase)
+        .param(NinaSmallArrayParam::new(ssid)?)
+        .param(NinaSmallArrayParam::new(passphrase)?);
+
+    Ok(Request::new(operation, |_| Ok(())))
+}
+
+pub(crate) fn set_key(
+    ssid: &str,
+    index: u8,
+    key: &str,
+) -> Result<Request<NinaAbstractParam, impl Parse<()>>, Error> {
+    let operation = Operation::new(NinaCommand::SetKey)
+        .param(NinaSmallArrayParam::new(ssid)?)
+        .param(NinaByteParam::from_bytes(&[index])?)
+        .param(NinaSmallArrayParam::new(key)?);
+
+    Ok(Request::new(operation, |_| Ok(())))
+}
+
+pub(crate) fn set_ap_net(
+    ssid: &str,
+    channel: u8,
+) -> Result<Request<NinaAbstractParam, impl Parse<()>>, Error> {
+    let operation = Operation::new(NinaCommand::SetApNet)
+        .param(NinaSmallArrayParam::new(ssid)?)
+        .param(NinaByteParam::from_bytes(&[channel])?);
+
+    Ok(Request::new(
+        operation,
+        succeeded(NetworkError::AccessPointFailed),
+    ))
+}
+
+pub(crate) fn set_ap_passphrase(
+    ssid: &str,
+    passphrase: &str,
+    channel: u8,
+) -> Result<Request<NinaAbstractParam, impl Parse<()>>, Error> {
+    let operation = Operation::new(NinaCommand::SetApPassphrase)
+        .param(NinaSmallArrayParam::new(ssid)?)
+        .param(NinaSmallArrayParam::new(passphrase)?)
+        .param(NinaByteParam::from_bytes(&[channel])?);
+
+    Ok(Request::new(
+        operation,
+        succeeded(NetworkError::AccessPointFailed),
+    ))
+}
+
+pub(crate) fn set_client_cert(
+    certificate: &[u8],
+) -> Result<Request<NinaBorrowedParam<'_>, impl Parse<()>>, Error> {
+    if certificate.len() > MAX_CLIENT_CERTIFICATE_LENGTH {
+        return Err(ProtocolError::PayloadTooLarge.into());
+    }
+
+    // NINA firmware clears its certificate buffer and copies the param into it on every
+    // SET_CLI_CERT, so it can't be uploaded in chunks: only the last one would be kept.
+    // Instead the whole certificate is sent as one param, which can be longer than
+    // MAX_NINA_LARGE_ARRAY_PARAM_BUFFER_LENGTH since it's borrowed from the caller's buffer
+    // and the firmware receives commands of up to 4092 bytes.
+    let operation = Operation::new_borrowed(NinaCommand::SetClientCert)
+        .param(NinaBorrowedParam::from_bytes(certificate)?);
+
+    Ok(Request::new(operation, |_| Ok(())))
+}
+
+pub(crate) fn set_private_key(
+    private_key: &[u8],
+) -> Result<Request<NinaBorrowedParam<'_>, impl Parse<()>>, Error> {
+    if private_key.len() > MAX_PRIVATE_KEY_LENGTH {
+        return Err(ProtocolError::PayloadTooLarge.into());
+    }
+
+    // Sent in a single command for the same reason as set_client_cert()
+    let operation = Operation::new_borrowed(NinaCommand::SetPrivateKey)
+        .param(NinaBorrowedParam::from_bytes(private_key)?);
+
+    Ok(Request::new(operation, |_| Ok(())))
+}
+
+pub(crate) fn set_ent_identity(
+    identity: &str,
+) -> Result<Request<NinaAbstractParam, impl Parse<()>>, Error> {
+    let operation =
+        Operation::new(NinaCommand::SetEntIdent).param(NinaSmallArrayParam::new(identity)?);
+
+    Ok(Request::new(operation, |_| Ok(())))
+}
+
+pub(crate) fn set_ent_username(
+    username: &str,
+) -> Result<Request<NinaAbstractParam, impl Parse<()>>, Error> {
+    let operation =
+        Operation::new(NinaCommand::SetEntUname).param(NinaSmallArrayParam::new(username)?);
+
+    Ok(Request::new(operation, |_| Ok(())))
+}
+
+pub(crate) fn set_ent_password(
+    password: &str,
+) -> Result<Request<NinaAbstractParam, impl Parse<()>>, Error> {
+    let operation =
+        Operation::new(NinaCommand::SetEntPasswd).param(NinaSmallArrayParam::new(password)?);
+
+    Ok(Request::new(operation, |_| Ok(())))
+}
+
+pub(crate) fn set_ent_ca_cert(
+    ca_cert: &[u8],
+) -> Result<Request<NinaBorrowedParam<'_>, impl Parse<()>>, Error> {
+    let operation = Operation::new_borrowed(NinaCommand::SetEntCaCert)
+        .param(NinaBorrowedParam::from_bytes(ca_cert)?);
+
+    Ok(Request::new(operation, |_| Ok(())))
+}
+
+pub(crate) fn set_ent_enable() -> Request<NinaAbstractParam, impl Parse<()>> {
+    Request::new(Operation::new(NinaCommand::SetEntEnable), |_| Ok(()))
+}
+
+pub(crate) fn get_conn_status() -> Request<NinaAbstractParam, impl Parse<ConnectionStatus>> {
+    Request::new(Operation::new(NinaCommand::GetConnStatus), |result| {
+        Ok(ConnectionStatus::from(result.param_as_u8(0)?))
+    })
+}
+
+pub(crate) fn get_reason_code() -> Request<NinaAbstractParam, impl Parse<DisconnectReason>> {
+    Request::new(Operation::new(NinaCommand::GetReasonCode), |result| {
+        Ok(DisconnectReason::from(result.param_as_u8(0)?))
+    })
+}
+
+pub(crate) fn disconnect() -> Request<NinaAbstractParam, impl Parse<()>> {
+    let operation = Operation::new(NinaCommand::Disconnect).param(dummy_param());
+
+    Request::new(operation, |_| Ok(()))
+}
+
+pub(crate) fn set_dns_config(
+    ip1: IpAddress,
+    ip2: Option<IpAddress>,
+) -> Result<Request<NinaAbstractParam, impl Parse<()>>, Error> {
+    // FIXME: refactor Operation so it can take different NinaParam types
+    let operation = Operation::new(NinaCommand::SetDNSConfig)
+        // FIXME: first param should be able to be a NinaByteParam:
+        .param(NinaByteParam::from_bytes(&[1])?)
+        .param(NinaSmallArrayParam::from_bytes(&ip1)?)
+        .param(NinaSmallArrayParam::from_bytes(&ip2.unwrap_or_default())?);
+
+    Ok(Request::new(operation, |_| Ok(())))
+}
+
+pub(crate) fn set_ip_config(
+    local_ip: IpAddress,
+    gateway: IpAddress,
+    subnet: IpAddress,
+) -> Result<Request<NinaAbstractParam, impl Parse<()>>, Error> {
+    // The first param tells NINA firmware how many of the following addresses are
+    // valid, in the order local IP, gateway, subnet
+    let operation = Operation::new(NinaCommand::SetIPConfig)
+        .param(NinaByteParam::from_bytes(&[3])?)
+        .param(NinaSmallArrayParam::from_bytes(&local_ip)?)
+        .param(NinaSmallArrayParam::from_bytes(&gateway)?)
+        .param(NinaSmallArrayParam::from_bytes(&subnet)?);
+
+    Ok(Request::new(operation, |_| Ok(())))
+}
+
+pub(crate) fn get_ip_addr() -> Request<NinaAbstractParam, impl Parse<IpConfig>> {
+    let operation = Operation::new(NinaCommand::GetIPAddr).param(dummy_param());
+
+    Request::new(operation, |result| {
+        Ok(IpConfig {
+            ip: result.param_as_array::<4>(0)?,
+            netmask: result.param_as_array::<4>(1)?,
+            gateway: result.param_as_array::<4>(2)?,
+        })
+    })
+    .expecting(Some(3))
+}
+
+pub(crate) fn get_mac_addr() -> Request<NinaAbstractParam, impl Parse<MacAddress>> {
+    let operation = Operation::new(NinaCommand::GetMacAddr).param(dummy_param());
+
+    Request::new(operation, |result| {
+        // NINA firmware sends the MAC address least significant byte first
+        let mut mac = result.param_as_array::<6>(0)?;
+        mac.reverse();
+
+        Ok(MacAddress(mac))
+    })
+}
+
+pub(crate) fn get_curr_ssid() -> Request<NinaAbstractParam, impl Parse<String<MAX_SSID_LENGTH>>> {
+    let operation = Operation::new(NinaCommand::GetCurrSsid).param(dummy_param());
+
+    Request::new(operation, |result| ssid_from_bytes(&result[0]))
+}
+
+pub(crate) fn get_curr_bssid() -> Request<NinaAbstractParam, impl Parse<MacAddress>> {
+    let operation = Operation::new(NinaCommand::GetCurrBssid).param(dummy_param());
+
+    Request::new(operation, |result| {
+        // NINA firmware sends the BSSID least significant byte first
+        let mut bssid = result.param_as_array::<6>(0)?;
+        bssid.reverse();
+
+        Ok(MacAddress(bssid))
+    })
+}
+
+pub(crate) fn get_curr_rssi() -> Request<NinaAbstractParam, impl Parse<Rssi>> {
+    let operation = Operation::new(NinaCommand::GetCurrRssi).param(dummy_param());
+
+    Request::new(operation, |result| {
+        Ok(Rssi(i32::from_le_bytes(result.param_as_array::<4>(0)?)))
+    })
+}
+
+pub(crate) fn get_curr_enct() -> Request<NinaAbstractParam, impl Parse<EncryptionType>> {
+    let operation = Operation::new(NinaCommand::GetCurrEnct).param(dummy_param());
+
+    Request::new(operation, |result| {
+        Ok(EncryptionType::from(result.param_as_u8(0)?))
+    })
+}
+
+pub(crate) fn req_host_by_name(
+    hostname: &str,
+) -> Result<Request<NinaAbstractParam, impl Parse<u8>>, Error> {
+    let operation =
+        Operation::new(NinaCommand::ReqHostByName).param(NinaSmallArrayParam::new(hostname)?);
+
+    Ok(Request::new(operation, |result| {
+        let result = result.param_as_u8(0)?;
+        if result != 1u8 {
+            return Err(NetworkError::DnsResolveFailed.into());
+        }
+
+        Ok(result)
+    }))
+}
+
+pub(crate) fn get_host_by_name() -> Request<NinaAbstractParam, impl Parse<IpAddress>> {
+    Request::new(Operation::new(NinaCommand::GetHostByName), |result| {
+        let ip_address = result.param_as_array::<4>(0)?;
+
+        // NINA firmware returns 255.255.255.255 when the hostname couldn't be resolved
+        if ip_address != [255, 255, 255, 255] {
+            Ok(ip_address)
+        } else {
+            Err(NetworkError::DnsResolveFailed.into())
+        }
+    })
+}
+
+pub(crate) fn get_socket() -> Request<NinaAbstractParam, impl Parse<Socket>> {
+    Request::new(Operation::new(NinaCommand::GetSocket), |result| {
+        let socket = result.param_as_u8(0)?;
+        if socket == NO_SOCKET_AVAILABLE {
+            return Err(NetworkError::NoSocketAvailable.into());
+        }
+
+        Ok(socket)
+    })
+}
+
+pub(crate) fn ping(
+    ip: IpAddress,
+    ttl: u8,
+) -> Result<Request<NinaAbstractParam, impl Parse<u16>>, Error> {
+    let operation = Operation::new(NinaCommand::Ping)
+        .param(NinaSmallArrayParam::from_bytes(&ip)?)
+        .param(NinaByteParam::from_bytes(&[ttl])?);
+
+    Ok(Request::new(operation, |result| {
+        // NINA firmware reports failures as negative round trip times
+        match i16::from_le_bytes(result.param_as_array::<2>(0)?) {
+            PING_TIMEOUT => Err(NetworkError::PingTimeout.into()),
+            PING_DEST_UNREACHABLE | PING_UNKNOWN_HOST => Err(NetworkError::HostUnreachable.into()),
+            round_trip_ms if round_trip_ms < 0 => Err(NetworkError::PingFailed.into()),
+            round_trip_ms => Ok(round_trip_ms as u16),
+        }
+    }))
+}
+
+pub(crate) fn get_time() -> Request<NinaAbstractParam, impl Parse<u32>> {
+    Request::new(Operation::new(NinaCommand::GetTime), |result| {
+        // NINA firmware reports 0 until it has synced time over SNTP
+        match u32::from_le_bytes(result.param_as_array::<4>(0)?) {
+            0 => Err(NetworkError::TimeNotSynced.into()),
+            seconds => Ok(seconds),
+        }
+    })
+}
+
+pub(crate) fn set_hostname(
+    hostname: &str,
+) -> Result<Request<NinaAbstractParam, impl Parse<()>>, Error> {
+    let operation =
+        Operation::new(NinaCommand::SetHostname).param(NinaSmallArrayParam::new(hostname)?);
+
+    Ok(Request::new(operation, |_| Ok(())))
+}
+
+pub(crate) fn start_client_tcp(
+    socket: Socket,
+    ip: IpAddress,
+    port: Port,
+    mode: &TransportMode,
+) -> Result<Request<NinaAbstractParam, impl Parse<()>>, Error> {
+    let port_as_bytes = [((port & 0xff00) >> 8) as u8, (port & 0xff) as u8];
+    let operation = Operation::new(NinaCommand::StartClientTcp)
+        .param(NinaSmallArrayParam::from_bytes(&ip)?)
+        .param(NinaWordParam::from_bytes(&port_as_bytes)?)
+        .param(NinaByteParam::from_bytes(&[socket])?)
+        .param(NinaByteParam::from_bytes(&[*mode as u8])?);
+
+    Ok(Request::new(operation, client_started(*mode)))
+}
+
+pub(crate) fn start_client_tcp_hostname(
+    socket: Socket,
+    hostname: &str,
+    port: Port,
+    mode: &TransportMode,
+) -> Result<Request<NinaAbstractParam, impl Parse<()>>, Error> {
+    // NINA firmware resolves the hostname itself when it's sent as the first param,
+    // so the IP address param is left empty
+    let port_as_bytes = [((port & 0xff00) >> 8) as u8, (port & 0xff) as u8];
+    let operation = Operation::new(NinaCommand::StartClientTcp)
+        .param(NinaSmallArrayParam::new(hostname)?)
+        .param(NinaSmallArrayParam::from_bytes(&[0, 0, 0, 0])?)
+        .param(NinaWordParam::from_bytes(&port_as_bytes)?)
+        .param(NinaByteParam::from_bytes(&[socket])?)
+        .param(NinaByteParam::from_bytes(&[*mode as u8])?);
+
+    Ok(Request::new(operation, client_started(*mode)))
+}
+
+pub(crate) fn stop_client_tcp(
+    socket: Socket,
+) -> Result<Request<NinaAbstractParam, impl Parse<()>>, Error> {
+    let operation =
+        Operation::new(NinaCommand::StopClientTcp).param(NinaByteParam::from_bytes(&[socket])?);
+
+    Ok(Request::new(
+        operation,
+        succeeded(NetworkError::DisconnectFailed),
+    ))
+}
+
+pub(crate) fn get_client_state_tcp(
+    socket: Socket,
+) -> Result<Request<NinaAbstractParam, impl Parse<ConnectionState>>, Error> {
+    let operation =
+        Operation::new(NinaCommand::GetClientStateTcp).param(NinaByteParam::from_bytes(&[socket])?);
+
+    // TODO: Determine whether or not any ConnectionState variants should be considered
+    // an error.
+    Ok(Request::new(operation, |result| {
+        Ok(ConnectionState::from(result.param_as_u8(0)?))
+    }))
+}
+
+pub(crate) fn start_server_tcp(
+    socket: Socket,
+    port: Port,
+    mode: &TransportMode,
+) -> Result<Request<NinaAbstractParam, impl Parse<()>>, Error> {
+    let port_as_bytes = [((port & 0xff00) >> 8) as u8, (port & 0xff) as u8];
+    let operation = Operation::new(NinaCommand::StartServerTcp)
+        .param(NinaWordParam::from_bytes(&port_as_bytes)?)
+        .param(NinaByteParam::from_bytes(&[socket])?)
+        .param(NinaByteParam::from_bytes(&[*mode as u8])?);
+
+    Ok(Request::new(operation, succeeded(NetworkError::BindFailed)))
+}
+
+pub(crate) fn get_server_state_tcp(
+    socket: Socket,
+) -> Result<Request<NinaAbstractParam, impl Parse<ConnectionState>>, Error> {
+    let operation =
+        Operation::new(NinaCommand::GetStateTcp).param(NinaByteParam::from_bytes(&[socket])?);
+
+    Ok(Request::new(operation, |result| {
+        Ok(ConnectionState::from(result.param_as_u8(0)?))
+    }))
+}
+
+// Written straight from the caller's buffer rather than copied into a param first
+pub(crate) fn send_data_tcp<'a>(
+    data: &'a [u8],
+    socket: &'a Socket,
+) -> Result<Request<NinaBorrowedParam<'a>, impl Parse<usize>>, Error> {
+    if data.len() > MAX_NINA_LARGE_ARRAY_PARAM_BUFFER_LENGTH {
+        return Err(ProtocolError::PayloadTooLarge.into());
+    }
+
+    let operation = Operation::new_borrowed(NinaCommand::SendDataTcp)
+        .param(NinaBorrowedParam::from_bytes(slice::from_ref(socket))?)
+        .param(NinaBorrowedParam::from_bytes(data)?);
+
+    // NINA firmware reports the number of bytes written as a little-endian u16
+    Ok(Request::new(operation, |result| {
+        Ok(u16::from_le_bytes(result.param_as_array::<2>(0)?) as usize)
+    }))
+}
+
+pub(crate) fn data_sent_tcp(
+    socket: Socket,
+) -> Result<Request<NinaAbstractParam, impl Parse<bool>>, Error> {
+    let operation =
+        Operation::new(NinaCommand::DataSentTcp).param(NinaByteParam::from_bytes(&[socket])?);
+
+    Ok(Request::new(operation, |result| {
+        Ok(result.param_as_u8(0)? == 1)
+    }))
+}
+
+pub(crate) fn avail_data_tcp(
+    socket: Socket,
+) -> Result<Request<NinaAbstractParam, impl Parse<usize>>, Error> {
+    let operation =
+        Operation::new(NinaCommand::AvailDataTcp).param(NinaByteParam::from_bytes(&[socket])?);
+
+    // NINA firmware reports the number of available bytes as a little-endian u16
+    Ok(Request::new(operation, |result| {
+        Ok(u16::from_le_bytes(result.param_as_array::<2>(0)?) as usize)
+    }))
+}
+
+pub(crate) fn get_data_tcp(
+    socket: Socket,
+    peek: bool,
+) -> Result<Request<NinaAbstractParam, impl Parse<u8>>, Error> {
+    // The peek flag is sent as a 2-byte param. Setting both bytes makes sure the
+    // firmware sees it regardless of which byte it inspects.
+    let peek_as_bytes = [peek as u8, peek as u8];
+    let operation = Operation::new(NinaCommand::GetDataTcp)
+        .param(NinaByteParam::from_bytes(&[socket])?)
+        .param(NinaWordParam::from_bytes(&peek_as_bytes)?);
+
+    Ok(Request::new(operation, |result| result.param_as_u8(0)))
+}
+
+pub(crate) fn get_databuf_tcp(
+    socket: Socket,
+    data: &mut [u8],
+) -> Result<Request<NinaAbstractParam, impl Parse<usize> + '_>, Error> {
+    // Never ask for more than what fits into a single NINA response
+    let length = data.len().min(MAX_NINA_RESPONSE_LENGTH) as u16;
+    let operation = Operation::new(NinaCommand::GetDatabufTcp)
+        .param(NinaLargeArrayParam::from_bytes(&[socket])?)
+        .param(NinaLargeArrayParam::from_bytes(&length.to_le_bytes())?);
+
+    Ok(Request::new(operation, move |result: &NinaResponse| {
+        let received = &result[0];
+        if received.len() > data.len() {
+            return Err(ProtocolError::PayloadTooLarge.into());
+        }
+
+        data[..received.len()].copy_from_slice(received);
+
+        Ok(received.len())
+    }))
+}
+
+// Appends to the datagram NINA firmware sends on SEND_DATA_UDP
+pub(crate) fn insert_databuf<'a>(
+    socket: &'a Socket,
+    data: &'a [u8],
+) -> Result<Request<NinaBorrowedParam<'a>, impl Parse<()>>, Error> {
+    if data.len() > MAX_NINA_LARGE_ARRAY_PARAM_BUFFER_LENGTH {
+        return Err(ProtocolError::PayloadTooLarge.into());
+    }
+
+    let operation = Operation::new_borrowed(NinaCommand::InsertDatabuf)
+        .param(NinaBorrowedParam::from_bytes(slice::from_ref(socket))?)
+        .param(NinaBorrowedParam::from_bytes(data)?);
+
+    Ok(Request::new(operation, succeeded(NetworkError::SendFailed)))
+}
+
+pub(crate) fn send_data_udp(
+    socket: Socket,
+) -> Result<Request<NinaAbstractParam, impl Parse<()>>, Error> {
+    let operation =
+        Operation::new(NinaCommand::SendDataUdp).param(NinaByteParam::from_bytes(&[socket])?);
+
+    Ok(Request::new(operation, succeeded(NetworkError::SendFailed)))
+}
+
+pub(crate) fn get_remote_data(
+    socket: Socket,
+) -> Result<Request<NinaAbstractParam, impl Parse<(IpAddress, Port)>>, Error> {
+    let operation =
+        Operation::new(NinaCommand::GetRemoteData).param(NinaByteParam::from_bytes(&[socket])?);
+
+    // NINA firmware reports the remote port in network byte order
+    Ok(Request::new(operation, |result| {
+        Ok((
+            result.param_as_array::<4>(0)?,
+            u16::from_be_bytes(result.param_as_array::<2>(1)?),
+        ))
+    })
+    .expecting(Some(2)))
+}
+
+pub(crate) fn start_scan_networks() -> Request<NinaAbstractParam, impl Parse<()>> {
+    Request::new(
+        Operation::new(NinaCommand::StartScanNetworks),
+        succeeded(NetworkError::ScanFailed),
+    )
+}
+
+// One SSID param is returned per network found
+pub(crate) fn get_scan_networks(
+) -> Request<NinaAbstractParam, impl Parse<Vec<String<MAX_SSID_LENGTH>, MAX_SCAN_RESULTS>>> {
+    Request::new(Operation::new(NinaCommand::ScanNetworks), |result| {
+        let mut ssids = Vec::new();
+        for ssid in result.params() {
+            // Can't overflow since NINA firmware reports at most one SSID per scan result
+            ssids.push(ssid_from_bytes(ssid)?).ok();
+        }
+
+        Ok(ssids)
+    })
+    .expecting(None)
+}
+
+pub(crate) fn get_idx_rssi(
+    index: u8,
+) -> Result<Request<NinaAbstractParam, impl Parse<Rssi>>, Error> {
+    let operation =
+        Operation::new(NinaCommand::GetIdxRssi).param(NinaByteParam::from_bytes(&[index])?);
+
+    Ok(Request::new(operation, |result| {
+        Ok(Rssi(i32::from_le_bytes(result.param_as_array::<4>(0)?)))
+    }))
+}
+
+pub(crate) fn get_idx_enct(
+    index: u8,
+) -> Result<Request<NinaAbstractParam, impl Parse<EncryptionType>>, Error> {
+    let operation =
+        Operation::new(NinaCommand::GetIdxEnct).param(NinaByteParam::from_bytes(&[index])?);
+
+    Ok(Request::new(operation, |result| {
+        Ok(EncryptionType::from(result.param_as_u8(0)?))
+    }))
+}
+
+pub(crate) fn get_idx_bssid(
+    index: u8,
+) -> Result<Request<NinaAbstractParam, impl Parse<[u8; 6]>>, Error> {
+    let operation =
+        Operation::new(NinaCommand::GetIdxBssid).param(NinaByteParam::from_bytes(&[index])?);
+
+    Ok(Request::new(operation, |result| {
+        // NINA firmware sends the BSSID least significant byte first
+        let mut bssid = result.param_as_array::<6>(0)?;
+        bssid.reverse();
+
+        Ok(bssid)
+    }))
+}
+
+pub(crate) fn get_idx_channel(
+    index: u8,
+) -> Result<Request<NinaAbstractParam, impl Parse<u8>>, Error> {
+    let operation =
+        Operation::new(NinaCommand::GetIdxChannel).param(NinaByteParam::from_bytes(&[index])?);
+
+    Ok(Request::new(operation, |result| result.param_as_u8(0)))
+}
+
+// Sent by commands that only take a param because NINA firmware expects one
+fn dummy_param() -> NinaByteParam {
+    NinaByteParam::from_bytes(&[ControlByte::Dummy as u8]).unwrap_or_default()
+}
+
+// SSIDs aren't guaranteed to be valid UTF-8, so keep only the valid prefix
+fn ssid_from_bytes(ssid: &[u8]) -> Result<String<MAX_SSID_LENGTH>, Error> {
+    if ssid.len() > MAX_SSID_LENGTH {
+        return Err(ProtocolError::PayloadTooLarge.into());
+    }
+
+    let ssid = match str::from_utf8(ssid) {
+        Ok(ssid) => ssid,
+        Err(e) => str::from_utf8(&ssid[..e.valid_up_to()]).unwrap_or_default(),
+    };
+
+    Ok(String::from(ssid))
+}
+
+// NINA firmware replies 1 when a command succeeded
+fn succeeded(error: NetworkError) -> impl Parse<()> {
+    move |result| {
+        if result.param_as_u8(0)? == 1 {
+            Ok(())
+        } else {
+            Err(error.into())
+        }
+    }
+}
+
+fn client_started(mode: TransportMode) -> impl Parse<()> {
+    move |result| {
+        if result.param_as_u8(0)? == 1 {
+            Ok(())
+        } else {
+            Err(connect_failed(&mode))
+        }
+    }
+}
+
+// NINA firmware doesn't report why a connection failed, but in TLS modes a failed
+// handshake is the most likely cause once the TCP connection itself is possible.
+fn connect_failed(mode: &TransportMode) -> Error {
+    if mode.is_tls() {
+        NetworkError::TlsHandshakeFailed.into()
+    } else {
+        NetworkError::ConnectFailed.into()
+    }
+}
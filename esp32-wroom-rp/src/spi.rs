@@ -5,157 +5,196 @@
 //!
 //! Note: Currently everything in this file is private and considered internal to the crate.
 //!
-use core::convert::Infallible;
+use core::fmt::Debug;
 
 use embedded_hal::blocking::delay::DelayMs;
 use embedded_hal::blocking::spi::Transfer;
 
+use heapless::{String, Vec};
+
 use super::gpio::EspControlInterface;
-use super::network::{ConnectionState, IpAddress, NetworkError, Port, Socket, TransportMode};
+use super::network::{
+    ConnectionState, IpAddress, IpConfig, MacAddress, NetworkError, Port, Socket, TransportMode,
+};
 use super::protocol::operation::Operation;
+use super::protocol::request::{self, Parse, Request};
 use super::protocol::{
-    NinaByteParam, NinaCommand, NinaConcreteParam, NinaLargeArrayParam, NinaParam,
-    NinaProtocolHandler, NinaResponseBuffer, NinaSmallArrayParam, NinaWordParam, ProtocolError,
-    ProtocolInterface, MAX_NINA_PARAMS, MAX_NINA_RESPONSE_LENGTH,
+    ClientCredentials, ControlByte, NinaParam, NinaProtocolHandler, NinaResponse,
+    ProtocolInterface, MAX_NINA_LARGE_ARRAY_PARAM_BUFFER_LENGTH,
 };
-use super::wifi::ConnectionStatus;
-use super::{Error, FirmwareVersion};
-
-#[repr(u8)]
-#[derive(Debug)]
-enum ControlByte {
-    Start = 0xE0u8,
-    End = 0xEEu8,
-    Reply = 1u8 << 7u8,
-    Dummy = 0xFFu8,
-    Error = 0xEFu8,
-}
+use super::tcp_client::ReceiveData;
+use super::wifi::{
+    ConnectionStatus, DisconnectReason, EncryptionType, Rssi, ScanResult, MAX_SCAN_RESULTS,
+    MAX_SSID_LENGTH,
+};
+use super::{BusError, Error, FirmwareVersion};
+
+// How many times to ask NINA firmware whether sent data was acknowledged before giving up
+pub(crate) const MAX_DATA_SENT_CHECKS: usize = 25;
+
+// How long to wait between those checks, the same as WiFiNINA does
+pub(crate) const DATA_SENT_CHECK_INTERVAL_MS: u16 = 100;
 
 // All SPI-specific aspects of the NinaProtocolHandler go here in this struct impl
 impl<S, C> ProtocolInterface for NinaProtocolHandler<S, C>
 where
     S: Transfer<u8>,
+    S::Error: Debug,
     C: EspControlInterface,
 {
-    fn init(&mut self) {
+    fn init(&mut self) -> Result<(), Error> {
         // Chip select is active-low, so we'll initialize it to a driven-high state
-        self.control_pins.init();
+        self.control_pins.init()
     }
 
-    fn reset<D: DelayMs<u16>>(&mut self, delay: &mut D) {
-        self.control_pins.reset(delay);
+    fn reset<D: DelayMs<u16>>(&mut self, delay: &mut D) -> Result<(), Error> {
+        self.control_pins.reset(delay)?;
+
+        // The ESP32 target loses its client certificate and private key on reset
+        self.client_credentials = ClientCredentials::default();
+        Ok(())
     }
 
     fn get_fw_version(&mut self) -> Result<FirmwareVersion, Error> {
-        let operation = Operation::new(NinaCommand::GetFwVersion);
+        self.request(request::get_fw_version())
+    }
 
-        self.execute(&operation)?;
+    fn set_network(&mut self, ssid: &str) -> Result<(), Error> {
+        self.request(request::set_network(ssid)?)
+    }
 
-        let result = self.receive(&operation, 1)?;
-        let (version, _) = result.split_at(5);
+    fn set_passphrase(&mut self, ssid: &str, passphrase: &str) -> Result<(), Error> {
+        self.request(request::set_passphrase(ssid, passphrase)?)
+    }
 
-        Ok(FirmwareVersion::new(version)) // e.g. 1.7.4
+    fn set_key(&mut self, ssid: &str, index: u8, key: &str) -> Result<(), Error> {
+        self.request(request::set_key(ssid, index, key)?)
     }
 
-    fn set_passphrase(&mut self, ssid: &str, passphrase: &str) -> Result<(), Error> {
-        let operation = Operation::new(NinaCommand::SetPassphrase)
-            .param(NinaSmallArrayParam::new(ssid)?)
-            .param(NinaSmallArrayParam::new(passphrase)?);
+    fn set_ap_net(&mut self, ssid: &str, channel: u8) -> Result<(), Error> {
+        self.request(request::set_ap_net(ssid, channel)?)
+    }
+
+    fn set_ap_passphrase(
+        &mut self,
+        ssid: &str,
+        passphrase: &str,
+        channel: u8,
+    ) -> Result<(), Error> {
+        self.request(request::set_ap_passphrase(ssid, passphrase, channel)?)
+    }
 
-        self.execute(&operation)?;
+    fn set_client_cert(&mut self, certificate: &[u8]) -> Result<(), Error> {
+        self.request(request::set_client_cert(certificate)?)?;
 
-        self.receive(&operation, 1)?;
+        self.client_credentials.certificate = true;
         Ok(())
     }
 
-    fn get_conn_status(&mut self) -> Result<ConnectionStatus, Error> {
-        let operation = Operation::new(NinaCommand::GetConnStatus);
+    fn set_private_key(&mut self, private_key: &[u8]) -> Result<(), Error> {
+        self.request(request::set_private_key(private_key)?)?;
 
-        self.execute(&operation)?;
-
-        let result = self.receive(&operation, 1)?;
+        self.client_credentials.private_key = true;
+        Ok(())
+    }
 
-        Ok(ConnectionStatus::from(result[0]))
+    fn set_ent_identity(&mut self, identity: &str) -> Result<(), Error> {
+        self.request(request::set_ent_identity(identity)?)
     }
 
-    fn disconnect(&mut self) -> Result<(), Error> {
-        let dummy_param = NinaByteParam::from_bytes(&[ControlByte::Dummy as u8]);
-        let operation =
-            Operation::new(NinaCommand::Disconnect).param(dummy_param.unwrap_or_default());
+    fn set_ent_username(&mut self, username: &str) -> Result<(), Error> {
+        self.request(request::set_ent_username(username)?)
+    }
 
-        self.execute(&operation)?;
+    fn set_ent_password(&mut self, password: &str) -> Result<(), Error> {
+        self.request(request::set_ent_password(password)?)
+    }
 
-        self.receive(&operation, 1)?;
+    fn set_ent_ca_cert(&mut self, ca_cert: &[u8]) -> Result<(), Error> {
+        self.request(request::set_ent_ca_cert(ca_cert)?)
+    }
 
-        Ok(())
+    fn set_ent_enable(&mut self) -> Result<(), Error> {
+        self.request(request::set_ent_enable())
     }
 
-    fn set_dns_config(&mut self, ip1: IpAddress, ip2: Option<IpAddress>) -> Result<(), Error> {
-        // FIXME: refactor Operation so it can take different NinaParam types
-        let operation = Operation::new(NinaCommand::SetDNSConfig)
-            // FIXME: first param should be able to be a NinaByteParam:
-            .param(NinaByteParam::from_bytes(&[1])?)
-            .param(NinaSmallArrayParam::from_bytes(&ip1)?)
-            .param(NinaSmallArrayParam::from_bytes(&ip2.unwrap_or_default())?);
+    fn get_conn_status(&mut self) -> Result<ConnectionStatus, Error> {
+        self.request(request::get_conn_status())
+    }
 
-        self.execute(&operation)?;
+    fn get_reason_code(&mut self) -> Result<DisconnectReason, Error> {
+        self.request(request::get_reason_code())
+    }
 
-        self.receive(&operation, 1)?;
+    fn disconnect(&mut self) -> Result<(), Error> {
+        self.request(request::disconnect())
+    }
 
-        Ok(())
+    fn set_dns_config(&mut self, ip1: IpAddress, ip2: Option<IpAddress>) -> Result<(), Error> {
+        self.request(request::set_dns_config(ip1, ip2)?)
     }
 
-    fn req_host_by_name(&mut self, hostname: &str) -> Result<u8, Error> {
-        let operation =
-            Operation::new(NinaCommand::ReqHostByName).param(NinaSmallArrayParam::new(hostname)?);
+    fn set_ip_config(
+        &mut self,
+        local_ip: IpAddress,
+        gateway: IpAddress,
+        subnet: IpAddress,
+    ) -> Result<(), Error> {
+        self.request(request::set_ip_config(local_ip, gateway, subnet)?)
+    }
 
-        self.execute(&operation)?;
+    fn get_ip_addr(&mut self) -> Result<IpConfig, Error> {
+        self.request(request::get_ip_addr())
+    }
 
-        let result = self.receive(&operation, 1)?;
+    fn get_mac_addr(&mut self) -> Result<MacAddress, Error> {
+        self.request(request::get_mac_addr())
+    }
 
-        if result[0] != 1u8 {
-            return Err(NetworkError::DnsResolveFailed.into());
-        }
+    fn get_curr_ssid(&mut self) -> Result<String<MAX_SSID_LENGTH>, Error> {
+        self.request(request::get_curr_ssid())
+    }
 
-        Ok(result[0])
+    fn get_curr_bssid(&mut self) -> Result<MacAddress, Error> {
+        self.request(request::get_curr_bssid())
     }
 
-    fn get_host_by_name(&mut self) -> Result<NinaResponseBuffer, Error> {
-        let operation = Operation::new(NinaCommand::GetHostByName);
+    fn get_curr_rssi(&mut self) -> Result<Rssi, Error> {
+        self.request(request::get_curr_rssi())
+    }
 
-        self.execute(&operation)?;
+    fn get_curr_enct(&mut self) -> Result<EncryptionType, Error> {
+        self.request(request::get_curr_enct())
+    }
 
-        let result = self.receive(&operation, 1)?;
+    fn req_host_by_name(&mut self, hostname: &str) -> Result<u8, Error> {
+        self.request(request::req_host_by_name(hostname)?)
+    }
 
-        Ok(result)
+    fn get_host_by_name(&mut self) -> Result<IpAddress, Error> {
+        self.request(request::get_host_by_name())
     }
 
     fn resolve(&mut self, hostname: &str) -> Result<IpAddress, Error> {
         self.req_host_by_name(hostname)?;
 
-        let dummy: IpAddress = [255, 255, 255, 255];
-
-        let result = self.get_host_by_name()?;
-
-        let (ip_slice, _) = result.split_at(4);
-        let mut ip_address: IpAddress = [0; 4];
-        ip_address.clone_from_slice(ip_slice);
-
-        if ip_address != dummy {
-            Ok(ip_address)
-        } else {
-            Err(NetworkError::DnsResolveFailed.into())
-        }
+        self.get_host_by_name()
     }
 
     fn get_socket(&mut self) -> Result<Socket, Error> {
-        let operation = Operation::new(NinaCommand::GetSocket);
+        self.request(request::get_socket())
+    }
 
-        self.execute(&operation)?;
+    fn ping(&mut self, ip: IpAddress, ttl: u8) -> Result<u16, Error> {
+        self.request(request::ping(ip, ttl)?)
+    }
 
-        let result = self.receive(&operation, 1)?;
+    fn get_time(&mut self) -> Result<u32, Error> {
+        self.request(request::get_time())
+    }
 
-        Ok(result[0])
+    fn set_hostname(&mut self, hostname: &str) -> Result<(), Error> {
+        self.request(request::set_hostname(hostname)?)
     }
 
     fn start_client_tcp(
@@ -165,248 +204,301 @@ where
         port: Port,
         mode: &TransportMode,
     ) -> Result<(), Error> {
-        let port_as_bytes = [((port & 0xff00) >> 8) as u8, (port & 0xff) as u8];
-        let operation = Operation::new(NinaCommand::StartClientTcp)
-            .param(NinaSmallArrayParam::from_bytes(&ip)?)
-            .param(NinaWordParam::from_bytes(&port_as_bytes)?)
-            .param(NinaByteParam::from_bytes(&[socket])?)
-            .param(NinaByteParam::from_bytes(&[*mode as u8])?);
+        self.client_credentials.check(mode)?;
 
-        self.execute(&operation)?;
+        self.request(request::start_client_tcp(socket, ip, port, mode)?)
+    }
 
-        let result = self.receive(&operation, 1)?;
-        if result[0] == 1 {
-            Ok(())
-        } else {
-            Err(NetworkError::ConnectFailed.into())
-        }
+    fn start_client_tcp_hostname(
+        &mut self,
+        socket: Socket,
+        hostname: &str,
+        port: Port,
+        mode: &TransportMode,
+    ) -> Result<(), Error> {
+        self.client_credentials.check(mode)?;
+
+        self.request(request::start_client_tcp_hostname(
+            socket, hostname, port, mode,
+        )?)
     }
 
     // TODO: passing in TransportMode but not using, for now. It will become a way
     // of stopping the right kind of client (e.g. TCP, vs UDP)
     fn stop_client_tcp(&mut self, socket: Socket, _mode: &TransportMode) -> Result<(), Error> {
-        let operation =
-            Operation::new(NinaCommand::StopClientTcp).param(NinaByteParam::from_bytes(&[socket])?);
-
-        self.execute(&operation)?;
-
-        let result = self.receive(&operation, 1)?;
-        if result[0] == 1 {
-            Ok(())
-        } else {
-            Err(NetworkError::DisconnectFailed.into())
-        }
+        self.request(request::stop_client_tcp(socket)?)
     }
 
     fn get_client_state_tcp(&mut self, socket: Socket) -> Result<ConnectionState, Error> {
-        let operation = Operation::new(NinaCommand::GetClientStateTcp)
-            .param(NinaByteParam::from_bytes(&[socket])?);
-
-        self.execute(&operation)?;
+        self.request(request::get_client_state_tcp(socket)?)
+    }
 
-        let result = self.receive(&operation, 1)?;
-        // TODO: Determine whether or not any ConnectionState variants should be considered
-        // an error.
-        Ok(ConnectionState::from(result[0]))
+    fn start_server_tcp(
+        &mut self,
+        socket: Socket,
+        port: Port,
+        mode: &TransportMode,
+    ) -> Result<(), Error> {
+        self.request(request::start_server_tcp(socket, port, mode)?)
     }
 
-    fn send_data(&mut self, data: &str, socket: Socket) -> Result<[u8; 1], Error> {
-        let operation = Operation::new(NinaCommand::SendDataTcp)
-            .param(NinaLargeArrayParam::from_bytes(&[socket])?)
-            .param(NinaLargeArrayParam::new(data)?);
+    fn get_server_state_tcp(&mut self, socket: Socket) -> Result<ConnectionState, Error> {
+        self.request(request::get_server_state_tcp(socket)?)
+    }
 
-        self.execute(&operation)?;
+    fn send_data<D: DelayMs<u16>>(
+        &mut self,
+        data: &[u8],
+        socket: Socket,
+        delay: &mut D,
+    ) -> Result<usize, Error> {
+        self.send_data_paced(data, socket, |_| {
+            delay.delay_ms(DATA_SENT_CHECK_INTERVAL_MS)
+        })
+    }
 
-        let result = self.receive(&operation, 1)?;
+    fn data_sent_tcp(&mut self, socket: Socket) -> Result<bool, Error> {
+        self.request(request::data_sent_tcp(socket)?)
+    }
 
-        Ok([result[0]])
+    fn avail_data_tcp(&mut self, socket: Socket) -> Result<usize, Error> {
+        self.request(request::avail_data_tcp(socket)?)
     }
-}
 
-impl<S, C> NinaProtocolHandler<S, C>
-where
-    S: Transfer<u8>,
-    C: EspControlInterface,
-{
-    fn execute<P: NinaParam>(&mut self, operation: &Operation<P>) -> Result<(), Error> {
-        let mut total_params_length: u16 = 0;
-        let mut total_params_length_size: u16 = 0;
-
-        self.control_pins.wait_for_esp_select();
-        let number_of_params: u8 = if !operation.params.is_empty() {
-            operation.params.len() as u8
-        } else {
-            0
-        };
-        let result = self.send_cmd(&operation.command, number_of_params);
-
-        // Only send params if they are present
-        if !operation.params.is_empty() {
-            operation.params.iter().for_each(|param| {
-                self.send_param(param).ok();
-
-                total_params_length += param.length();
-                total_params_length_size += param.length_size() as u16;
-            });
-
-            self.send_end_cmd().ok();
-
-            // This is to make sure we align correctly
-            // 4 (start byte, command byte, number of params as byte, end byte)
-            // + the number of bytes to represent the param length (1 or 2)
-            // + the sum of all param lengths
-            // See https://github.com/arduino/nina-fw/blob/master/main/CommandHandler.cpp#L2153 for the actual equation.
-            let command_size: u16 = 4u16 + total_params_length_size + total_params_length;
-            self.pad_to_multiple_of_4(command_size);
-        }
-        self.control_pins.esp_deselect();
+    fn get_data_tcp(&mut self, socket: Socket, peek: bool) -> Result<u8, Error> {
+        self.request(request::get_data_tcp(socket, peek)?)
+    }
 
-        result
+    fn get_databuf_tcp(&mut self, socket: Socket, data: &mut [u8]) -> Result<usize, Error> {
+        self.request(request::get_databuf_tcp(socket, data)?)
     }
 
-    fn receive<P: NinaParam>(
-        &mut self,
-        operation: &Operation<P>,
-        expected_num_params: u8,
-    ) -> Result<NinaResponseBuffer, Error> {
-        self.control_pins.wait_for_esp_select();
+    fn receive_data(&mut self, socket: Socket, data: &mut [u8]) -> Result<usize, Error> {
+        let mut receive_data = ReceiveData::new(data);
 
-        self.check_response_ready(&operation.command, expected_num_params)?;
+        while !receive_data.is_done() {
+            let available = self.avail_data_tcp(socket)?;
+            let bytes_read = match receive_data.next_buffer(available) {
+                Some(buffer) => self.get_databuf_tcp(socket, buffer)?,
+                None => 0,
+            };
+            receive_data.received(bytes_read);
+        }
 
-        let result = self.read_response()?;
+        Ok(receive_data.bytes_read())
+    }
 
-        self.control_pins.esp_deselect();
+    fn insert_databuf(&mut self, socket: Socket, data: &[u8]) -> Result<(), Error> {
+        self.request(request::insert_databuf(&socket, data)?)
+    }
 
-        Ok(result)
+    fn send_data_udp(&mut self, socket: Socket) -> Result<(), Error> {
+        self.request(request::send_data_udp(socket)?)
     }
 
-    fn send_cmd(&mut self, cmd: &NinaCommand, num_params: u8) -> Result<(), Error> {
-        let buf: [u8; 3] = [
-            ControlByte::Start as u8,
-            (*cmd as u8) & !(ControlByte::Reply as u8),
-            num_params,
-        ];
+    fn get_remote_data(&mut self, socket: Socket) -> Result<(IpAddress, Port), Error> {
+        self.request(request::get_remote_data(socket)?)
+    }
 
-        for byte in buf {
-            let write_buf = &mut [byte];
-            self.bus.borrow_mut().transfer(write_buf).ok();
-        }
+    fn send_datagram(
+        &mut self,
+        socket: Socket,
+        ip: IpAddress,
+        port: Port,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        self.start_client_tcp(socket, ip, port, &TransportMode::Udp)?;
 
-        if num_params == 0 {
-            self.send_end_cmd().ok();
+        // NINA firmware keeps appending to the same datagram until it's sent
+        for chunk in data.chunks(MAX_NINA_LARGE_ARRAY_PARAM_BUFFER_LENGTH) {
+            self.insert_databuf(socket, chunk)?;
         }
-        Ok(())
-    }
 
-    fn read_response(&mut self) -> Result<NinaResponseBuffer, Error> {
-        let response_length_in_bytes = self.get_byte().ok().unwrap() as usize;
+        self.send_data_udp(socket)
+    }
 
-        if response_length_in_bytes > MAX_NINA_PARAMS {
-            return Err(ProtocolError::TooManyParameters.into());
+    fn receive_datagram(
+        &mut self,
+        socket: Socket,
+        data: &mut [u8],
+    ) -> Result<Option<(usize, IpAddress, Port)>, Error> {
+        let available = self.avail_data_tcp(socket)?;
+        if available == 0 {
+            return Ok(None);
         }
 
-        let mut response_param_buffer: NinaResponseBuffer = [0; MAX_NINA_RESPONSE_LENGTH];
-        if response_length_in_bytes > 0 {
-            response_param_buffer =
-                self.read_response_bytes(response_param_buffer, response_length_in_bytes)?;
+        // Don't ask for availability again while reading, as that makes NINA firmware
+        // move on to the next datagram once this one has been read
+        let length = data.len().min(available);
+        let mut bytes_read: usize = 0;
+        while bytes_read < length {
+            let result = self.get_databuf_tcp(socket, &mut data[bytes_read..length])?;
+            if result == 0 {
+                break;
+            }
+
+            bytes_read += result;
         }
 
-        let control_byte: u8 = ControlByte::End as u8;
-        self.read_and_check_byte(&control_byte).ok();
+        let (ip, port) = self.get_remote_data(socket)?;
 
-        Ok(response_param_buffer)
+        Ok(Some((bytes_read, ip, port)))
     }
 
-    fn check_response_ready(&mut self, cmd: &NinaCommand, num_params: u8) -> Result<(), Error> {
-        self.check_start_cmd()?;
-        let byte_to_check: u8 = *cmd as u8 | ControlByte::Reply as u8;
-        let result = self.read_and_check_byte(&byte_to_check).ok().unwrap();
-        // Ensure we see a cmd byte
-        if !result {
-            return Err(ProtocolError::InvalidCommand.into());
-        }
-
-        let result = self.read_and_check_byte(&num_params).unwrap();
-        // Ensure we see the number of params we expected to receive back
-        if !result {
-            return Err(ProtocolError::InvalidNumberOfParameters.into());
-        }
-        Ok(())
+    fn start_scan_networks(&mut self) -> Result<(), Error> {
+        self.request(request::start_scan_networks())
     }
 
-    fn read_response_bytes(
+    fn get_scan_networks(
         &mut self,
-        mut response_param_buffer: NinaResponseBuffer,
-        response_length_in_bytes: usize,
-    ) -> Result<NinaResponseBuffer, Error> {
-        for byte in response_param_buffer
-            .iter_mut()
-            .take(response_length_in_bytes)
-        {
-            *byte = self.get_byte().ok().unwrap();
-        }
-        Ok(response_param_buffer)
+    ) -> Result<Vec<String<MAX_SSID_LENGTH>, MAX_SCAN_RESULTS>, Error> {
+        self.request(request::get_scan_networks())
     }
 
-    fn send_end_cmd(&mut self) -> Result<(), Infallible> {
-        let end_command: &mut [u8] = &mut [ControlByte::End as u8];
-        self.bus.borrow_mut().transfer(end_command).ok();
-        Ok(())
+    fn get_idx_rssi(&mut self, index: u8) -> Result<Rssi, Error> {
+        self.request(request::get_idx_rssi(index)?)
+    }
+
+    fn get_idx_enct(&mut self, index: u8) -> Result<EncryptionType, Error> {
+        self.request(request::get_idx_enct(index)?)
+    }
+
+    fn get_idx_bssid(&mut self, index: u8) -> Result<[u8; 6], Error> {
+        self.request(request::get_idx_bssid(index)?)
+    }
+
+    fn get_idx_channel(&mut self, index: u8) -> Result<u8, Error> {
+        self.request(request::get_idx_channel(index)?)
     }
 
-    fn get_byte(&mut self) -> Result<u8, Infallible> {
-        let word_out = &mut [ControlByte::Dummy as u8];
-        let word = self.bus.borrow_mut().transfer(word_out).ok().unwrap();
-        Ok(word[0])
+    fn scan_networks(&mut self) -> Result<Vec<ScanResult, MAX_SCAN_RESULTS>, Error> {
+        self.start_scan_networks()?;
+
+        let ssids = self.get_scan_networks()?;
+
+        let mut scan_results: Vec<ScanResult, MAX_SCAN_RESULTS> = Vec::new();
+        for (index, ssid) in ssids.into_iter().enumerate() {
+            let index = index as u8;
+
+            let scan_result = ScanResult {
+                ssid,
+                rssi: self.get_idx_rssi(index)?,
+                encryption: self.get_idx_enct(index)?,
+                bssid: self.get_idx_bssid(index)?,
+                channel: self.get_idx_channel(index)?,
+            };
+            // Can't overflow since there is at most one SSID per scan result
+            scan_results.push(scan_result).ok();
+        }
+
+        Ok(scan_results)
     }
+}
 
-    fn wait_for_byte(&mut self, wait_byte: u8) -> Result<bool, Error> {
-        let retry_limit: u16 = 1000u16;
+impl<S, C> NinaProtocolHandler<S, C>
+where
+    S: Transfer<u8>,
+    S::Error: Debug,
+    C: EspControlInterface,
+{
+    // Like send_data(), but for callers that have no delay to pass in, e.g. the embedded-io
+    // and embedded-nal traits. The control pins pace the checks instead, which only wait
+    // when they have a time source.
+    pub(crate) fn send_data_with_pins_delay(
+        &mut self,
+        data: &[u8],
+        socket: Socket,
+    ) -> Result<usize, Error> {
+        self.send_data_paced(data, socket, |control_pins| {
+            control_pins.delay_ms(DATA_SENT_CHECK_INTERVAL_MS)
+        })
+    }
+
+    // Only counts the bytes as sent once NINA firmware acknowledges them, calling wait()
+    // between the checks
+    fn send_data_paced(
+        &mut self,
+        data: &[u8],
+        socket: Socket,
+        mut wait: impl FnMut(&mut C),
+    ) -> Result<usize, Error> {
+        let written = self.request(request::send_data_tcp(data, &socket)?)?;
+        if written == 0 {
+            return Ok(0);
+        }
 
-        for _ in 0..retry_limit {
-            let byte_read = self.get_byte().ok().unwrap();
-            if byte_read == ControlByte::Error as u8 {
-                // consume remaining bytes after error: 0x00, 0xEE
-                self.get_byte().ok();
-                self.get_byte().ok();
-                return Err(ProtocolError::NinaProtocolVersionMismatch.into());
-            } else if byte_read == wait_byte {
-                return Ok(true);
+        for check in 0..MAX_DATA_SENT_CHECKS {
+            if check > 0 {
+                wait(&mut self.control_pins);
+            }
+            if self.data_sent_tcp(socket)? {
+                return Ok(written);
             }
         }
-        Err(ProtocolError::CommunicationTimeout.into())
+
+        Err(NetworkError::SendFailed.into())
     }
 
-    fn check_start_cmd(&mut self) -> Result<bool, Error> {
-        self.wait_for_byte(ControlByte::Start as u8)
+    // Sends a request shared with the async protocol handler and interprets the response
+    fn request<P: NinaParam, T>(&mut self, request: Request<P, impl Parse<T>>) -> Result<T, Error> {
+        self.execute(&request.operation)?;
+
+        let result = self.receive(&request.operation, request.num_params)?;
+
+        (request.parse)(&result)
     }
 
-    fn read_and_check_byte(&mut self, check_byte: &u8) -> Result<bool, Infallible> {
-        let byte = self.get_byte().ok().unwrap();
-        Ok(&byte == check_byte)
+    fn execute<P: NinaParam>(&mut self, operation: &Operation<P>) -> Result<(), Error> {
+        self.control_pins
+            .wait_for_esp_select_timeout(self.command_timeout_ms)?;
+
+        let result = self.send_operation(operation);
+
+        // Always release the ESP32 target, even when sending the command failed
+        self.control_pins.esp_deselect()?;
+
+        result
     }
 
-    fn send_param<P: NinaParam>(&mut self, param: &P) -> Result<(), Infallible> {
-        self.send_param_length(param)?;
-        for byte in param.data().iter() {
-            self.bus.borrow_mut().transfer(&mut [*byte]).ok();
+    fn send_operation<P: NinaParam>(&mut self, operation: &Operation<P>) -> Result<(), Error> {
+        for byte in operation.bytes() {
+            self.transfer_byte(byte)?;
         }
         Ok(())
     }
 
-    fn send_param_length<P: NinaParam>(&mut self, param: &P) -> Result<(), Infallible> {
-        let bytes = param.length_as_bytes();
-        for byte in bytes.iter().take(param.length_size() as usize) {
-            self.bus.borrow_mut().transfer(&mut [*byte]).ok();
-        }
-        Ok(())
+    // Receives a response with `expected_num_params` params, or any number of them if None
+    // (e.g. the list of SSIDs returned from a WiFi scan).
+    fn receive<P: NinaParam>(
+        &mut self,
+        operation: &Operation<P>,
+        expected_num_params: Option<u8>,
+    ) -> Result<NinaResponse, Error> {
+        self.control_pins
+            .wait_for_esp_select_timeout(self.command_timeout_ms)?;
+
+        let result =
+            NinaResponse::read(&operation.command, expected_num_params, || self.get_byte());
+
+        // Always release the ESP32 target, even when the response was invalid
+        self.control_pins.esp_deselect()?;
+
+        result
     }
 
-    fn pad_to_multiple_of_4(&mut self, mut command_size: u16) {
-        while command_size % 4 != 0 {
-            self.get_byte().ok();
-            command_size += 1;
-        }
+    fn get_byte(&mut self) -> Result<u8, Error> {
+        self.transfer_byte(ControlByte::Dummy as u8)
+    }
+
+    // Transfers a single byte over the SPI bus, returning the byte read back
+    fn transfer_byte(&mut self, byte: u8) -> Result<u8, Error> {
+        let word = &mut [byte];
+        let result = self
+            .bus
+            .borrow_mut()
+            .transfer(word)
+            .map_err(BusError::transfer)?[0];
+        Ok(result)
     }
 }
 
@@ -414,7 +506,8 @@ where
 mod spi_tests {
     use super::*;
 
-    use crate::gpio::EspControlPins;
+    use crate::gpio::{EspControlPins, DEFAULT_COMMAND_TIMEOUT_MS};
+    use crate::protocol::ProtocolError;
     use crate::Error;
     use core::cell::RefCell;
     use core::str;
@@ -478,7 +571,9 @@ mod spi_tests {
 
         let mut protocol_handler = NinaProtocolHandler {
             bus: RefCell::new(transfer_mock),
-            control_pins: control_pins,
+            control_pins,
+            command_timeout_ms: DEFAULT_COMMAND_TIMEOUT_MS,
+            client_credentials: ClientCredentials::default(),
         };
 
         let result = protocol_handler.set_passphrase(str_slice, "");
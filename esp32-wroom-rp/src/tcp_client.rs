@@ -13,7 +13,7 @@
 //!     port,
 //!     mode,
 //!     &mut delay,
-//!     &mut |tcp_client| {
+//!     &mut |tcp_client, delay| {
 //!         defmt::info!(
 //!             "TCP connection to {:?}:{:?} successful",
 //!             hostname,
@@ -21,9 +21,9 @@
 //!         );
 //!         defmt::info!("Hostname: {:?}", tcp_client.server_hostname());
 //!         defmt::info!("Sending HTTP Document: {:?}", http_document.as_str());
-//!         match tcp_client.send_data(&http_document) {
-//!             Ok(response) => {
-//!                 defmt::info!("Response: {:?}", response)
+//!         match tcp_client.send_data(http_document.as_bytes(), delay) {
+//!             Ok(length) => {
+//!                 defmt::info!("Sent {:?} bytes", length)
 //!             }
 //!             Err(e) => {
 //!                 defmt::error!("Response error: {:?}", e)
@@ -40,28 +40,87 @@
 //! }
 //! ```
 //!
+//! To connect using TLS, pass [`TransportMode::Tls`] along with the server's hostname, which
+//! the ESP32 target needs to verify the server's certificate:
+//!
+//! ```no_run
+//! let connection = TcpClient::build(&mut wifi).open("github.com", 443, TransportMode::Tls, &mut delay)?;
+//! ```
+//!
+//! A connection can also be kept open across loop iterations by opening a [`TcpConnection`],
+//! which is closed when [`TcpConnection::close`] is called or when it is dropped:
+//!
+//! ```no_run
+//! let mut connection = TcpClient::build(&mut wifi).open(hostname, port, mode, &mut delay)?;
+//!
+//! loop {
+//!     connection.send(http_document.as_bytes(), &mut delay)?;
+//!
+//!     let mut response = [0u8; 512];
+//!     let length = connection.receive(&mut response)?;
+//!     defmt::info!("Response: {:?}", &response[..length]);
+//!
+//!     if connection.state()? != ConnectionState::Established {
+//!         break;
+//!     }
+//! }
+//!
+//! connection.close()?;
+//! ```
+//!
+
+use core::fmt::Debug;
 
 use embedded_hal::blocking::delay::DelayMs;
 use embedded_hal::blocking::spi::Transfer;
 
+use embedded_io::{ErrorType, Read, Write};
+
 use heapless::String;
 
 use super::gpio::EspControlInterface;
 use super::network::{
     ConnectionState, Hostname, IpAddress, NetworkError, Port, Socket, TransportMode,
 };
-use super::protocol::{NinaProtocolHandler, ProtocolInterface};
+use super::protocol::{
+    NinaProtocolHandler, ProtocolInterface, MAX_NINA_LARGE_ARRAY_PARAM_BUFFER_LENGTH,
+};
 use super::wifi::Wifi;
 use super::Error;
 
 const MAX_HOSTNAME_LENGTH: usize = 255;
 
+// How many times in a row to resend a frame that the ESP32 target accepted none of
+const MAX_SEND_RETRIES: usize = 10;
+
+// FIXME: without this delay after starting a client, we'll frequently see timing issues and
+// receive a CmdResponseErr. We may not be handling busy/ack flag handling properly and needs
+// further investigation. I suspect that the ESP32 isn't ready to receive another command
+// yet. (copied this from POC)
+pub(crate) const START_CLIENT_DELAY_MS: u16 = 250;
+
+// How long to wait between polls of a started client's state until it's established
+pub(crate) const ESTABLISH_POLL_INTERVAL_MS: u16 = 100;
+
+// How many times to poll a started client's state before giving up on it
+const MAX_ESTABLISH_POLLS: u16 = 10_000;
+
+/// The time in milliseconds between polls of the ESP32 target while a [`TcpConnection`] waits
+/// for data to read.
+pub const RECEIVE_POLL_INTERVAL_MS: u16 = 10;
+
 /// Allows for a [`TcpClient`] instance to connect to a remote server by providing
 /// either a [`Hostname`] or an [`IpAddress`]. This trait also makes it possible to
 /// implement and support IPv6 addresses.
-pub trait Connect<'a, S, B, C> {
-    /// Enable a client to connect to `server` on `port` using transport layer `mode`.
-    fn connect<F: FnMut(&mut TcpClient<'a, B, C>), D: DelayMs<u16>>(
+pub trait Connect<'a, S, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    /// Enable a client to connect to `server` on `port` using transport layer `mode`. Once
+    /// connected, `f` is called with the client and `delay`, e.g. to send data with.
+    fn connect<F: FnMut(&mut TcpClient<'a, B, C>, &mut D), D: DelayMs<u16>>(
         &mut self,
         server: S,
         port: Port,
@@ -69,6 +128,18 @@ pub trait Connect<'a, S, B, C> {
         delay: &mut D,
         f: &mut F,
     ) -> Result<(), Error>;
+
+    /// Open a connection to `server` on `port` using transport layer `mode` that stays open
+    /// until [`TcpConnection::close`] is called or the returned [`TcpConnection`] is dropped.
+    fn open<D: DelayMs<u16>>(
+        self,
+        server: S,
+        port: Port,
+        mode: TransportMode,
+        delay: &mut D,
+    ) -> Result<TcpConnection<'a, B, C>, Error>
+    where
+        Self: Sized;
 }
 
 /// A client type that connects to and performs send/receive operations with a remote
@@ -85,9 +156,10 @@ pub struct TcpClient<'a, B, C> {
 impl<'a, B, C> Connect<'a, IpAddress, B, C> for TcpClient<'a, B, C>
 where
     B: Transfer<u8>,
+    B::Error: Debug,
     C: EspControlInterface,
 {
-    fn connect<F: FnMut(&mut TcpClient<'a, B, C>), D: DelayMs<u16>>(
+    fn connect<F: FnMut(&mut TcpClient<'a, B, C>, &mut D), D: DelayMs<u16>>(
         &mut self,
         ip: IpAddress,
         port: Port,
@@ -95,23 +167,32 @@ where
         delay: &mut D,
         f: &mut F,
     ) -> Result<(), Error> {
-        let socket = self.get_socket()?;
-        self.socket = Some(socket);
-        self.server_ip_address = Some(ip);
-        self.server_hostname = Some(String::new());
-        self.port = port;
-        self.mode = mode;
+        self.prepare(Some(ip), "", port, mode)?;
 
         self.connect_common(delay, f)
     }
+
+    fn open<D: DelayMs<u16>>(
+        mut self,
+        ip: IpAddress,
+        port: Port,
+        mode: TransportMode,
+        delay: &mut D,
+    ) -> Result<TcpConnection<'a, B, C>, Error> {
+        self.prepare(Some(ip), "", port, mode)?;
+        self.establish(delay)?;
+
+        Ok(TcpConnection { client: self })
+    }
 }
 
 impl<'a, B, C> Connect<'a, Hostname<'_>, B, C> for TcpClient<'a, B, C>
 where
     B: Transfer<u8>,
+    B::Error: Debug,
     C: EspControlInterface,
 {
-    fn connect<F: FnMut(&mut TcpClient<'a, B, C>), D: DelayMs<u16>>(
+    fn connect<F: FnMut(&mut TcpClient<'a, B, C>, &mut D), D: DelayMs<u16>>(
         &mut self,
         server_hostname: Hostname,
         port: Port,
@@ -119,19 +200,29 @@ where
         delay: &mut D,
         f: &mut F,
     ) -> Result<(), Error> {
-        let socket = self.get_socket()?;
-        self.socket = Some(socket);
-        self.server_hostname = Some(server_hostname.into()); // into() makes a copy of the &str slice
-        self.port = port;
-        self.mode = mode;
+        self.prepare(None, server_hostname, port, mode)?;
 
         self.connect_common(delay, f)
     }
+
+    fn open<D: DelayMs<u16>>(
+        mut self,
+        server_hostname: Hostname,
+        port: Port,
+        mode: TransportMode,
+        delay: &mut D,
+    ) -> Result<TcpConnection<'a, B, C>, Error> {
+        self.prepare(None, server_hostname, port, mode)?;
+        self.establish(delay)?;
+
+        Ok(TcpConnection { client: self })
+    }
 }
 
 impl<'a, B, C> TcpClient<'a, B, C>
 where
     B: Transfer<u8>,
+    B::Error: Debug,
     C: EspControlInterface,
 {
     /// Build a new instance of a [`TcpClient`] provided a [`Wifi`] instance.
@@ -175,68 +266,445 @@ where
         self.protocol_handler.get_socket()
     }
 
-    /// Send a string slice of data to a connected server.
-    pub fn send_data(&mut self, data: &str) -> Result<[u8; 1], Error> {
+    /// Send `data` to a connected server, returning the number of bytes the ESP32 target
+    /// acknowledged as sent. This may be fewer than `data.len()` if only part of it was sent.
+    /// `delay` paces the checks for that acknowledgement.
+    pub fn send_data<D: DelayMs<u16>>(
+        &mut self,
+        data: &[u8],
+        delay: &mut D,
+    ) -> Result<usize, Error> {
+        self.protocol_handler
+            .send_data(data, self.socket.unwrap_or_default(), delay)
+    }
+
+    /// Send all of `data` to a connected server, however long it is. It's split into frames
+    /// that fit into a single NINA command, and whatever the ESP32 target didn't acknowledge
+    /// of a frame is sent again. Fails with `NetworkError::SendFailed` if the ESP32 target
+    /// repeatedly accepts none of it.
+    pub fn send_all<D: DelayMs<u16>>(&mut self, data: &[u8], delay: &mut D) -> Result<(), Error> {
+        let socket = self.socket.unwrap_or_default();
+        let mut send_all = SendAll::new(data);
+
+        while let Some(frame) = send_all.next_frame() {
+            let written = self.protocol_handler.send_data(frame, socket, delay)?;
+            send_all.sent(written)?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the number of bytes sent by the connected server that are ready to be read.
+    pub fn available(&mut self) -> Result<usize, Error> {
+        self.protocol_handler
+            .avail_data_tcp(self.socket.unwrap_or_default())
+    }
+
+    /// Receive data sent by the connected server into `data`, returning the number of bytes read.
+    /// Reads whatever is currently available up to `data.len()` bytes and does not wait for more
+    /// data to arrive, so the returned count may be less than `data.len()` (or 0).
+    pub fn receive(&mut self, data: &mut [u8]) -> Result<usize, Error> {
+        self.protocol_handler
+            .receive_data(self.socket.unwrap_or_default(), data)
+    }
+
+    /// Look at the next byte of data sent by the connected server without consuming it.
+    /// Returns `None` if no data is currently available.
+    pub fn peek(&mut self) -> Result<Option<u8>, Error> {
+        if self.available()? == 0 {
+            return Ok(None);
+        }
+
         self.protocol_handler
-            .send_data(data, self.socket.unwrap_or_default())
+            .get_data_tcp(self.socket.unwrap_or_default(), true)
+            .map(Some)
+    }
+
+    // Requests a new socket and records the remote server details used by establish().
+    // An empty server_hostname means the server is addressed by server_ip_address.
+    fn prepare(
+        &mut self,
+        server_ip_address: Option<IpAddress>,
+        server_hostname: Hostname,
+        port: Port,
+        mode: TransportMode,
+    ) -> Result<(), Error> {
+        let socket = self.get_socket()?;
+        self.socket = Some(socket);
+        self.server_ip_address = server_ip_address;
+        self.server_hostname = Some(server_hostname.into()); // into() makes a copy of the &str slice
+        self.port = port;
+        self.mode = mode;
+
+        Ok(())
     }
 
     // Provides the in-common connect() functionality used by the public interface's
     // connect(ip_address) or connect(hostname) instances.
-    fn connect_common<F: FnMut(&mut TcpClient<'a, B, C>), D: DelayMs<u16>>(
+    fn connect_common<F: FnMut(&mut TcpClient<'a, B, C>, &mut D), D: DelayMs<u16>>(
         &mut self,
         delay: &mut D,
         mut f: F,
     ) -> Result<(), Error> {
+        self.establish(delay)?;
+
+        f(self, delay);
+
+        self.stop()
+    }
+
+    // Starts the client on the remote server and waits for the connection to be
+    // established. The socket is stopped again if the connection can't be established.
+    fn establish<D: DelayMs<u16>>(&mut self, delay: &mut D) -> Result<(), Error> {
         let socket = self.socket.unwrap_or_default();
         let mode = self.mode;
         let mut ip = self.server_ip_address.unwrap_or_default();
         let hostname = self.server_hostname.as_ref().unwrap();
         let port = self.port;
 
-        if !hostname.is_empty() {
-            ip = self
-                .protocol_handler
-                .resolve(hostname.as_str())
-                .unwrap_or_default();
+        if hostname.is_empty() {
+            self.protocol_handler
+                .start_client_tcp(socket, ip, port, &mode)?;
+        } else if mode.is_tls() {
+            // The hostname is needed by the ESP32 target to verify the server's certificate
+            self.protocol_handler.start_client_tcp_hostname(
+                socket,
+                hostname.as_str(),
+                port,
+                &mode,
+            )?;
+        } else {
+            ip = self.protocol_handler.resolve(hostname.as_str())?;
+            self.server_ip_address = Some(ip);
+
+            self.protocol_handler
+                .start_client_tcp(socket, ip, port, &mode)?;
         }
 
-        self.protocol_handler
-            .start_client_tcp(socket, ip, port, &mode)?;
+        delay.delay_ms(START_CLIENT_DELAY_MS);
 
-        // FIXME: without this delay, we'll frequently see timing issues and receive
-        // a CmdResponseErr. We may not be handling busy/ack flag handling properly
-        // and needs further investigation. I suspect that the ESP32 isn't ready to
-        // receive another command yet. (copied this from POC)
-        delay.delay_ms(250);
+        let mut establishing = Establishing::new();
 
-        let mut retry_limit = 10_000;
+        loop {
+            let state = self.protocol_handler.get_client_state_tcp(socket);
+            match establishing.check(state) {
+                Ok(true) => return Ok(()),
+                Ok(false) => delay.delay_ms(ESTABLISH_POLL_INTERVAL_MS),
+                Err(error) => {
+                    self.stop()?;
 
-        while retry_limit > 0 {
-            match self.protocol_handler.get_client_state_tcp(socket) {
-                Ok(ConnectionState::Established) => {
-                    f(self);
+                    return Err(error);
+                }
+            }
+        }
+    }
 
-                    self.protocol_handler.stop_client_tcp(socket, &mode)?;
+    // Stops the client on the current socket, if there is one.
+    fn stop(&mut self) -> Result<(), Error> {
+        match self.socket.take() {
+            Some(socket) => self.protocol_handler.stop_client_tcp(socket, &self.mode),
+            None => Ok(()),
+        }
+    }
+}
 
-                    return Ok(());
-                }
-                Ok(_status) => {
-                    delay.delay_ms(100);
-                    retry_limit -= 1;
-                }
-                Err(error) => {
-                    // At this point any error will likely be a protocol level error.
-                    // We do not currently consider any ConnectionState variants as errors.
-                    self.protocol_handler.stop_client_tcp(socket, &mode)?;
+// Splits the data passed to send_all() into frames that fit into a single NINA command and
+// keeps track of what's left to send, so the blocking and async clients resend it alike.
+pub(crate) struct SendAll<'d> {
+    remaining: &'d [u8],
+    retries: usize,
+}
 
-                    return Err(error);
+impl<'d> SendAll<'d> {
+    pub(crate) fn new(data: &'d [u8]) -> Self {
+        Self {
+            remaining: data,
+            retries: 0,
+        }
+    }
+
+    // The next frame to send, or None once all of the data was sent.
+    pub(crate) fn next_frame(&self) -> Option<&'d [u8]> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+
+        Some(&self.remaining[..self.frame_length()])
+    }
+
+    // Records that the ESP32 target wrote `written` bytes of the last frame. Whatever it
+    // didn't write is part of the next frame, until it accepts none of it too many times.
+    pub(crate) fn sent(&mut self, written: usize) -> Result<(), Error> {
+        if written == 0 {
+            self.retries += 1;
+            if self.retries > MAX_SEND_RETRIES {
+                return Err(NetworkError::SendFailed.into());
+            }
+        } else {
+            self.retries = 0;
+            self.remaining = &self.remaining[written.min(self.frame_length())..];
+        }
+
+        Ok(())
+    }
+
+    fn frame_length(&self) -> usize {
+        self.remaining
+            .len()
+            .min(MAX_NINA_LARGE_ARRAY_PARAM_BUFFER_LENGTH)
+    }
+}
+
+// Reads whatever data is available into the buffer passed to receive_data(), which NINA
+// firmware may hand over in several parts, so the blocking and async handlers stop alike.
+pub(crate) struct ReceiveData<'d> {
+    data: &'d mut [u8],
+    bytes_read: usize,
+    done: bool,
+}
+
+impl<'d> ReceiveData<'d> {
+    pub(crate) fn new(data: &'d mut [u8]) -> Self {
+        Self {
+            data,
+            bytes_read: 0,
+            done: false,
+        }
+    }
+
+    // Whether the buffer is full or there is nothing more to read right now
+    pub(crate) fn is_done(&self) -> bool {
+        self.done || self.bytes_read == self.data.len()
+    }
+
+    // The part of the buffer to read the `available` bytes into, or None if there are none
+    pub(crate) fn next_buffer(&mut self, available: usize) -> Option<&mut [u8]> {
+        if available == 0 {
+            return None;
+        }
+
+        let end = self.data.len().min(self.bytes_read + available);
+        Some(&mut self.data[self.bytes_read..end])
+    }
+
+    // Records that `bytes_read` bytes were read into the last buffer. Reading stops once
+    // NINA firmware hands over nothing, even if it reported data as available.
+    pub(crate) fn received(&mut self, bytes_read: usize) {
+        self.bytes_read += bytes_read;
+        self.done = bytes_read == 0;
+    }
+
+    pub(crate) fn bytes_read(&self) -> usize {
+        self.bytes_read
+    }
+}
+
+// Decides what to do with each ConnectionState polled while a started client is being
+// established, so the blocking and async clients give up on it alike.
+pub(crate) struct Establishing {
+    polls: u16,
+}
+
+impl Establishing {
+    pub(crate) fn new() -> Self {
+        Self { polls: 0 }
+    }
+
+    // Returns true once the connection is established, or false to poll again after
+    // ESTABLISH_POLL_INTERVAL_MS. Fails with the polling error, or with ConnectionTimeout
+    // after MAX_ESTABLISH_POLLS, in which case the caller stops the socket.
+    pub(crate) fn check(&mut self, state: Result<ConnectionState, Error>) -> Result<bool, Error> {
+        // At this point any error will likely be a protocol level error.
+        // We do not currently consider any ConnectionState variants as errors.
+        if state? == ConnectionState::Established {
+            return Ok(true);
+        }
+
+        self.polls += 1;
+        if self.polls >= MAX_ESTABLISH_POLLS {
+            return Err(NetworkError::ConnectionTimeout.into());
+        }
+
+        Ok(false)
+    }
+}
+
+/// An open connection to a remote server returned by [`Connect::open`]. The connection
+/// stays open until [`TcpConnection::close`] is called or it is dropped.
+pub struct TcpConnection<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    pub(crate) client: TcpClient<'a, B, C>,
+}
+
+impl<'a, B, C> TcpConnection<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    /// Send `data` to the connected server, returning the number of bytes sent.
+    /// See [`TcpClient::send_data`].
+    pub fn send<D: DelayMs<u16>>(&mut self, data: &[u8], delay: &mut D) -> Result<usize, Error> {
+        self.client.send_data(data, delay)
+    }
+
+    /// Send all of `data` to the connected server, however long it is.
+    /// See [`TcpClient::send_all`].
+    pub fn send_all<D: DelayMs<u16>>(&mut self, data: &[u8], delay: &mut D) -> Result<(), Error> {
+        self.client.send_all(data, delay)
+    }
+
+    /// Receive data sent by the connected server into `data`, returning the number of bytes read.
+    /// See [`TcpClient::receive`].
+    pub fn receive(&mut self, data: &mut [u8]) -> Result<usize, Error> {
+        self.client.receive(data)
+    }
+
+    /// Get the number of bytes sent by the connected server that are ready to be read.
+    pub fn available(&mut self) -> Result<usize, Error> {
+        self.client.available()
+    }
+
+    /// Look at the next byte of data sent by the connected server without consuming it.
+    /// Returns `None` if no data is currently available.
+    pub fn peek(&mut self) -> Result<Option<u8>, Error> {
+        self.client.peek()
+    }
+
+    /// Get the current [`ConnectionState`] of the connection as reported by the ESP32 target.
+    pub fn state(&mut self) -> Result<ConnectionState, Error> {
+        self.client
+            .protocol_handler
+            .get_client_state_tcp(self.client.socket.unwrap_or_default())
+    }
+
+    /// Get the [`IpAddress`] of the connected server. For a connection accepted by a
+    /// [`TcpServer`](super::tcp_server::TcpServer), this is the address of the remote client.
+    pub fn server_ip_address(&self) -> Option<IpAddress> {
+        self.client.server_ip_address()
+    }
+
+    /// Get the [`Hostname`] of the connected server, or an empty string if the connection
+    /// was opened with an [`IpAddress`].
+    pub fn server_hostname(&self) -> &str {
+        self.client.server_hostname()
+    }
+
+    /// Get the [`Port`] of the connected server. For a connection accepted by a
+    /// [`TcpServer`](super::tcp_server::TcpServer), this is the port of the remote client.
+    pub fn port(&self) -> Port {
+        self.client.port()
+    }
+
+    /// Get the [`TransportMode`] used in communication with the connected server.
+    pub fn mode(&self) -> TransportMode {
+        self.client.mode()
+    }
+
+    /// Close the connection, reporting any error that occurs while doing so.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.client.stop()
+    }
+}
+
+impl<'a, B, C> ErrorType for TcpConnection<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    type Error = Error;
+}
+
+impl<'a, B, C> Read for TcpConnection<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    /// Wait until data sent by the connected server is available and read as much of it as fits
+    /// into `buf`. Returns `0` once the server has closed the connection and all of its data
+    /// has been read. NINA firmware is polled every [`RECEIVE_POLL_INTERVAL_MS`] milliseconds
+    /// while waiting if the control pins provide a time source, e.g. a
+    /// [`TimeoutEspControl`](super::gpio::TimeoutEspControl).
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let socket = self.client.socket.unwrap_or_default();
+        loop {
+            let available = self.client.protocol_handler.avail_data_tcp(socket)?;
+            if available > 0 {
+                let length = buf.len().min(available);
+                let bytes_read = self
+                    .client
+                    .protocol_handler
+                    .get_databuf_tcp(socket, &mut buf[..length])?;
+                // NINA firmware can report data as available before it hands it over
+                if bytes_read > 0 {
+                    return Ok(bytes_read);
                 }
+            } else if self.state()? != ConnectionState::Established {
+                // The server may have sent more data right before closing the connection
+                if self.client.protocol_handler.avail_data_tcp(socket)? == 0 {
+                    return Ok(0);
+                }
+                continue;
             }
+
+            self.client
+                .protocol_handler
+                .control_pins
+                .delay_ms(RECEIVE_POLL_INTERVAL_MS);
         }
+    }
+}
 
-        self.protocol_handler.stop_client_tcp(socket, &mode)?;
+impl<'a, B, C> Write for TcpConnection<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    /// Send as much of `buf` to the connected server as fits into a single NINA command,
+    /// returning the number of bytes the ESP32 target reports as written. As with reading,
+    /// the checks that the data was sent are only paced if the control pins provide a time
+    /// source.
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
 
-        Err(NetworkError::ConnectionTimeout.into())
+        let length = buf.len().min(MAX_NINA_LARGE_ARRAY_PARAM_BUFFER_LENGTH);
+        match self
+            .client
+            .protocol_handler
+            .send_data_with_pins_delay(&buf[..length], self.client.socket.unwrap_or_default())?
+        {
+            0 => Err(NetworkError::SendFailed.into()),
+            written => Ok(written),
+        }
+    }
+
+    /// NINA firmware sends data as soon as it's written, so there is nothing to flush.
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, B, C> Drop for TcpConnection<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    fn drop(&mut self) {
+        // Errors can't be reported from drop(), call close() to observe them
+        self.client.stop().ok();
     }
 }
@@ -0,0 +1,149 @@
+//! Listen for and accept TCP connections from remote clients.
+//!
+//! ## Usage
+//!
+//! ```no_run
+//! let mut tcp_server = TcpServer::build(&mut wifi);
+//! tcp_server.listen(80)?;
+//!
+//! loop {
+//!     if let Some(mut connection) = tcp_server.accept()? {
+//!         defmt::info!(
+//!             "Client connected from {:?}:{:?}",
+//!             connection.server_ip_address(),
+//!             connection.port()
+//!         );
+//!
+//!         let mut request = [0u8; 512];
+//!         let length = connection.receive(&mut request)?;
+//!         defmt::info!("Request: {:?}", &request[..length]);
+//!
+//!         connection.send(http_document.as_bytes(), &mut delay)?;
+//!         connection.close()?;
+//!     }
+//! }
+//! ```
+//!
+
+use core::fmt::Debug;
+
+use embedded_hal::blocking::spi::Transfer;
+
+use heapless::String;
+
+use super::gpio::EspControlInterface;
+use super::network::{ConnectionState, Port, Socket, TransportMode};
+use super::protocol::{NinaProtocolHandler, ProtocolInterface};
+use super::tcp_client::{TcpClient, TcpConnection};
+use super::wifi::Wifi;
+use super::Error;
+
+// Reported by NINA firmware when no client is waiting on a server socket
+const NO_SOCKET_AVAILABLE: usize = 255;
+
+/// A server type that listens on a local port for TCP connections from remote clients.
+/// The server stops listening when [`TcpServer::close`] is called or when it is dropped.
+pub struct TcpServer<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    pub(crate) protocol_handler: &'a mut NinaProtocolHandler<B, C>,
+    pub(crate) socket: Option<Socket>,
+    pub(crate) port: Port,
+}
+
+impl<'a, B, C> TcpServer<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    /// Build a new instance of a [`TcpServer`] provided a [`Wifi`] instance.
+    pub fn build(wifi: &'a mut Wifi<B, C>) -> Self {
+        Self {
+            protocol_handler: wifi.protocol_handler.get_mut(),
+            socket: None,
+            port: 0,
+        }
+    }
+
+    /// Start listening for connections from remote clients on local `port`.
+    pub fn listen(&mut self, port: Port) -> Result<(), Error> {
+        let socket = self.protocol_handler.get_socket()?;
+        self.socket = Some(socket);
+        self.port = port;
+
+        self.protocol_handler
+            .start_server_tcp(socket, port, &TransportMode::Tcp)
+    }
+
+    /// Get the local [`Port`] set by calling [`TcpServer::listen`].
+    pub fn port(&self) -> Port {
+        self.port
+    }
+
+    /// Get the current [`ConnectionState`] of the server as reported by the ESP32 target,
+    /// e.g. [`ConnectionState::Listening`].
+    pub fn state(&mut self) -> Result<ConnectionState, Error> {
+        self.protocol_handler
+            .get_server_state_tcp(self.socket.unwrap_or_default())
+    }
+
+    /// Check for a remote client that has sent data to the server, returning a
+    /// [`TcpConnection`] on the socket the ESP32 target assigned to it. Returns `None`
+    /// without waiting if no such client is currently available.
+    pub fn accept(&mut self) -> Result<Option<TcpConnection<'_, B, C>>, Error> {
+        let socket = match self.socket {
+            Some(socket) => socket,
+            None => return Ok(None),
+        };
+
+        let client_socket = self.protocol_handler.avail_data_tcp(socket)?;
+        if client_socket == NO_SOCKET_AVAILABLE {
+            return Ok(None);
+        }
+
+        let client_socket = client_socket as Socket;
+        let (ip, port) = self.protocol_handler.get_remote_data(client_socket)?;
+
+        Ok(Some(TcpConnection {
+            client: TcpClient {
+                protocol_handler: &mut *self.protocol_handler,
+                socket: Some(client_socket),
+                server_ip_address: Some(ip),
+                port,
+                mode: TransportMode::Tcp,
+                server_hostname: Some(String::new()),
+            },
+        }))
+    }
+
+    /// Stop listening for connections, reporting any error that occurs while doing so.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.stop()
+    }
+
+    // Stops the server on the current socket, if there is one.
+    fn stop(&mut self) -> Result<(), Error> {
+        match self.socket.take() {
+            Some(socket) => self
+                .protocol_handler
+                .stop_client_tcp(socket, &TransportMode::Tcp),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a, B, C> Drop for TcpServer<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    fn drop(&mut self) {
+        // Errors can't be reported from drop(), call close() to observe them
+        self.stop().ok();
+    }
+}
@@ -0,0 +1,140 @@
+//! Send/receive datagrams to/from remote hosts using the UDP protocol.
+//!
+//! ## Usage
+//!
+//! ```no_run
+//! let mut udp_socket = UdpSocket::build(&mut wifi);
+//!
+//! // Only needed to receive datagrams that aren't replies to ones sent from this socket
+//! udp_socket.bind(8888)?;
+//!
+//! let ntp_server: IpAddress = [162, 159, 200, 1];
+//! udp_socket.send_to(ntp_server, 123, &ntp_request)?;
+//!
+//! let mut response = [0u8; 48];
+//! loop {
+//!     if let Some((length, ip, port)) = udp_socket.recv_from(&mut response)? {
+//!         defmt::info!("Received {:?} bytes from {:?}:{:?}", length, ip, port);
+//!         break;
+//!     }
+//! }
+//!
+//! udp_socket.close()?;
+//! ```
+//!
+
+use core::fmt::Debug;
+
+use embedded_hal::blocking::spi::Transfer;
+
+use super::gpio::EspControlInterface;
+use super::network::{IpAddress, Port, Socket, TransportMode};
+use super::protocol::{NinaProtocolHandler, ProtocolInterface};
+use super::wifi::Wifi;
+use super::Error;
+
+/// A socket that sends and receives datagrams to/from remote hosts using the UDP protocol.
+/// The socket is released when [`UdpSocket::close`] is called or when it is dropped.
+pub struct UdpSocket<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    pub(crate) protocol_handler: &'a mut NinaProtocolHandler<B, C>,
+    pub(crate) socket: Option<Socket>,
+    pub(crate) local_port: Option<Port>,
+}
+
+impl<'a, B, C> UdpSocket<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    /// Build a new instance of a [`UdpSocket`] provided a [`Wifi`] instance.
+    pub fn build(wifi: &'a mut Wifi<B, C>) -> Self {
+        Self {
+            protocol_handler: wifi.protocol_handler.get_mut(),
+            socket: None,
+            local_port: None,
+        }
+    }
+
+    /// Listen for datagrams sent to local `port`.
+    pub fn bind(&mut self, port: Port) -> Result<(), Error> {
+        let socket = self.socket()?;
+        self.protocol_handler
+            .start_server_tcp(socket, port, &TransportMode::Udp)?;
+        self.local_port = Some(port);
+
+        Ok(())
+    }
+
+    /// Get the local [`Port`] set by calling [`UdpSocket::bind`].
+    pub fn local_port(&self) -> Option<Port> {
+        self.local_port
+    }
+
+    /// Send `data` as a single datagram to `port` on the remote host at `ip`.
+    pub fn send_to(&mut self, ip: IpAddress, port: Port, data: &[u8]) -> Result<(), Error> {
+        let socket = self.socket()?;
+        self.protocol_handler.send_datagram(socket, ip, port, data)
+    }
+
+    /// Receive the next datagram into `data`, returning the number of bytes read along with
+    /// the [`IpAddress`] and [`Port`] of the sender. Returns `None` if no datagram is currently
+    /// available. If `data` is too small to hold the whole datagram, the rest of it is
+    /// returned by the next call.
+    pub fn recv_from(
+        &mut self,
+        data: &mut [u8],
+    ) -> Result<Option<(usize, IpAddress, Port)>, Error> {
+        let socket = match self.socket {
+            Some(socket) => socket,
+            None => return Ok(None),
+        };
+
+        self.protocol_handler.receive_datagram(socket, data)
+    }
+
+    /// Release the socket, reporting any error that occurs while doing so.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.stop()
+    }
+
+    // Requests a socket from NINA firmware the first time one is needed.
+    fn socket(&mut self) -> Result<Socket, Error> {
+        match self.socket {
+            Some(socket) => Ok(socket),
+            None => {
+                let socket = self.protocol_handler.get_socket()?;
+                self.socket = Some(socket);
+
+                Ok(socket)
+            }
+        }
+    }
+
+    // Stops the client on the current socket, if there is one.
+    fn stop(&mut self) -> Result<(), Error> {
+        match self.socket.take() {
+            Some(socket) => self
+                .protocol_handler
+                .stop_client_tcp(socket, &TransportMode::Udp),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a, B, C> Drop for UdpSocket<'a, B, C>
+where
+    B: Transfer<u8>,
+    B::Error: Debug,
+    C: EspControlInterface,
+{
+    fn drop(&mut self) {
+        // Errors can't be reported from drop(), call close() to observe them
+        self.stop().ok();
+    }
+}
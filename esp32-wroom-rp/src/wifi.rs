@@ -53,14 +53,20 @@
 //!
 
 use core::cell::RefCell;
+use core::fmt::Debug;
 
 use defmt::{write, Format, Formatter};
 
 use embedded_hal::blocking::{delay::DelayMs, spi::Transfer};
 
-use super::gpio::EspControlInterface;
-use super::network::IpAddress;
-use super::protocol::{NinaProtocolHandler, ProtocolInterface};
+use heapless::{String, Vec};
+
+use super::gpio::{EspControlInterface, DEFAULT_COMMAND_TIMEOUT_MS};
+use super::network::{IpAddress, IpConfig, MacAddress, NetworkError};
+use super::protocol::{
+    ClientCredentials, NinaProtocolHandler, ProtocolError, ProtocolInterface,
+    MAX_NINA_SMALL_ARRAY_PARAM_BUFFER_LENGTH,
+};
 use super::{Error, FirmwareVersion};
 
 /// An enumerated type that represents the current WiFi network connection status.
@@ -137,6 +143,281 @@ impl Format for ConnectionStatus {
     }
 }
 
+/// The reason the ESP32 target last disconnected from, or failed to join, a WiFi network.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum DisconnectReason {
+    /// No reason was given
+    Unspecified,
+    /// Authentication with the access point expired
+    AuthExpired,
+    /// The access point deauthenticated the device because it's leaving
+    AuthLeave,
+    /// Association with the access point expired
+    AssocExpired,
+    /// The access point has too many associated stations
+    AssocTooMany,
+    /// A message integrity check failed, usually because of a wrong passphrase
+    MicFailure,
+    /// The WPA 4-way handshake timed out, usually because of a wrong passphrase
+    FourWayHandshakeTimeout,
+    /// The WPA group key update timed out
+    GroupKeyUpdateTimeout,
+    /// WPA2-Enterprise (802.1X) authentication failed
+    EnterpriseAuthFailed,
+    /// Beacons from the access point stopped arriving
+    BeaconTimeout,
+    /// No access point with the SSID was found
+    NoApFound,
+    /// Authentication with the access point failed
+    AuthFailed,
+    /// Association with the access point failed
+    AssocFailed,
+    /// The handshake with the access point timed out
+    HandshakeTimeout,
+    /// The connection to the access point failed
+    ConnectionFailed,
+    /// Any other reason code reported by the device
+    Other(u8),
+}
+
+impl From<u8> for DisconnectReason {
+    fn from(reason_code: u8) -> DisconnectReason {
+        match reason_code {
+            1 => DisconnectReason::Unspecified,
+            2 => DisconnectReason::AuthExpired,
+            3 => DisconnectReason::AuthLeave,
+            4 => DisconnectReason::AssocExpired,
+            5 => DisconnectReason::AssocTooMany,
+            14 => DisconnectReason::MicFailure,
+            15 => DisconnectReason::FourWayHandshakeTimeout,
+            16 => DisconnectReason::GroupKeyUpdateTimeout,
+            23 => DisconnectReason::EnterpriseAuthFailed,
+            200 => DisconnectReason::BeaconTimeout,
+            201 => DisconnectReason::NoApFound,
+            202 => DisconnectReason::AuthFailed,
+            203 => DisconnectReason::AssocFailed,
+            204 => DisconnectReason::HandshakeTimeout,
+            205 => DisconnectReason::ConnectionFailed,
+            _ => DisconnectReason::Other(reason_code),
+        }
+    }
+}
+
+impl Format for DisconnectReason {
+    fn format(&self, fmt: Formatter) {
+        match self {
+            DisconnectReason::Unspecified => write!(fmt, "Unspecified"),
+            DisconnectReason::AuthExpired => write!(fmt, "Authentication expired"),
+            DisconnectReason::AuthLeave => write!(fmt, "Deauthenticated by access point"),
+            DisconnectReason::AssocExpired => write!(fmt, "Association expired"),
+            DisconnectReason::AssocTooMany => write!(fmt, "Access point has too many stations"),
+            DisconnectReason::MicFailure => write!(fmt, "Message integrity check failed"),
+            DisconnectReason::FourWayHandshakeTimeout => write!(fmt, "4-way handshake timed out"),
+            DisconnectReason::GroupKeyUpdateTimeout => write!(fmt, "Group key update timed out"),
+            DisconnectReason::EnterpriseAuthFailed => {
+                write!(fmt, "802.1X authentication failed")
+            }
+            DisconnectReason::BeaconTimeout => write!(fmt, "Beacon timed out"),
+            DisconnectReason::NoApFound => write!(fmt, "No access point found"),
+            DisconnectReason::AuthFailed => write!(fmt, "Authentication failed"),
+            DisconnectReason::AssocFailed => write!(fmt, "Association failed"),
+            DisconnectReason::HandshakeTimeout => write!(fmt, "Handshake timed out"),
+            DisconnectReason::ConnectionFailed => write!(fmt, "Connection failed"),
+            DisconnectReason::Other(reason_code) => write!(fmt, "Reason code {}", reason_code),
+        }
+    }
+}
+
+/// The maximum number of WiFi networks NINA firmware reports from a single scan.
+pub const MAX_SCAN_RESULTS: usize = 10;
+
+/// The maximum length in bytes of a WiFi network SSID.
+pub const MAX_SSID_LENGTH: usize = 32;
+
+// How often to check the connection status while waiting to join a WiFi network
+const JOIN_POLL_INTERVAL_MS: u16 = 100;
+
+// WPA passphrases are 8 to 63 ASCII characters, or a 64 hex digit pre-shared key
+const MIN_WPA_PASSPHRASE_LENGTH: usize = 8;
+const MAX_WPA_PASSPHRASE_LENGTH: usize = 64;
+
+// WEP keys are 5 or 13 ASCII characters, or 10 or 26 hex digits, in one of 4 key slots
+const WEP_KEY_LENGTHS: [usize; 4] = [5, 10, 13, 26];
+const MAX_WEP_KEY_INDEX: u8 = 3;
+
+/// The maximum length in bytes of a WPA2-Enterprise identity the ESP32 target can store.
+pub const MAX_ENTERPRISE_IDENTITY_LENGTH: usize = 32;
+
+/// The maximum length in bytes of a WPA2-Enterprise username or password the ESP32
+/// target can store.
+pub const MAX_ENTERPRISE_CREDENTIAL_LENGTH: usize = 128;
+
+/// The maximum length in bytes of a PEM encoded client certificate the ESP32 target can store.
+/// NINA firmware copies it into a fixed 1300 byte buffer.
+pub const MAX_CLIENT_CERTIFICATE_LENGTH: usize = 1300;
+
+/// The maximum length in bytes of a PEM encoded private key the ESP32 target can store.
+/// NINA firmware copies it into a fixed 1700 byte buffer.
+pub const MAX_PRIVATE_KEY_LENGTH: usize = 1700;
+
+/// An enumerated type that represents the encryption used by a WiFi network.
+#[repr(u8)]
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum EncryptionType {
+    /// WPA using TKIP
+    Tkip = 2,
+    /// WPA2 using CCMP (AES)
+    Ccmp = 4,
+    /// Legacy WEP
+    Wep = 5,
+    /// Open network without encryption
+    None = 7,
+    /// Mixed WPA/WPA2 mode
+    Auto = 8,
+    /// Unexpected value returned from device
+    Unknown = 255,
+}
+
+impl From<u8> for EncryptionType {
+    fn from(encryption_type: u8) -> EncryptionType {
+        match encryption_type {
+            2 => EncryptionType::Tkip,
+            4 => EncryptionType::Ccmp,
+            5 => EncryptionType::Wep,
+            7 => EncryptionType::None,
+            8 => EncryptionType::Auto,
+            _ => EncryptionType::Unknown,
+        }
+    }
+}
+
+impl Format for EncryptionType {
+    fn format(&self, fmt: Formatter) {
+        match self {
+            EncryptionType::Tkip => write!(fmt, "WPA (TKIP)"),
+            EncryptionType::Ccmp => write!(fmt, "WPA2 (CCMP)"),
+            EncryptionType::Wep => write!(fmt, "WEP"),
+            EncryptionType::None => write!(fmt, "None"),
+            EncryptionType::Auto => write!(fmt, "WPA/WPA2 (Auto)"),
+            EncryptionType::Unknown => write!(fmt, "Unknown"),
+        }
+    }
+}
+
+/// Received signal strength of a WiFi network in dBm.
+#[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Clone, Copy)]
+pub struct Rssi(pub i32);
+
+impl Format for Rssi {
+    fn format(&self, fmt: Formatter) {
+        write!(fmt, "{} dBm", self.0);
+    }
+}
+
+/// A WiFi network (access point) found by [`Wifi::scan`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScanResult {
+    /// Name of the network
+    pub ssid: String<MAX_SSID_LENGTH>,
+    /// Received signal strength
+    pub rssi: Rssi,
+    /// Encryption used by the network
+    pub encryption: EncryptionType,
+    /// MAC address of the access point
+    pub bssid: [u8; 6],
+    /// WiFi channel the access point is broadcasting on
+    pub channel: u8,
+}
+
+impl Format for ScanResult {
+    fn format(&self, fmt: Formatter) {
+        write!(
+            fmt,
+            "SSID: {}, RSSI: {}, Encryption: {}, BSSID: {:02x}, Channel: {}",
+            self.ssid.as_str(),
+            self.rssi,
+            self.encryption,
+            self.bssid,
+            self.channel
+        )
+    }
+}
+
+/// The credentials used to join a WiFi network with [`Wifi::connect`].
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum JoinCredentials<'a> {
+    /// Open network without encryption
+    Open,
+    /// WPA/WPA2 personal network protected by a passphrase
+    Wpa(&'a str),
+    /// Legacy WEP network protected by the `key` stored in slot `index` (0 to 3)
+    Wep {
+        /// Key slot the network uses
+        index: u8,
+        /// 5 or 13 ASCII characters, or 10 or 26 hex digits
+        key: &'a str,
+    },
+    /// WPA2-Enterprise (802.1X) network, e.g. using PEAP/MSCHAPv2
+    Enterprise {
+        /// Outer identity, often anonymous
+        identity: &'a str,
+        /// Username to authenticate with
+        username: &'a str,
+        /// Password to authenticate with
+        password: &'a str,
+        /// PEM encoded certificate to verify the authentication server against
+        ca_cert: Option<&'a [u8]>,
+    },
+}
+
+impl<'a> JoinCredentials<'a> {
+    // Checks lengths before anything is sent, as NINA firmware doesn't report bad credentials
+    // until it fails to join the network.
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        let valid = match self {
+            JoinCredentials::Open => true,
+            JoinCredentials::Wpa(passphrase) => {
+                (MIN_WPA_PASSPHRASE_LENGTH..=MAX_WPA_PASSPHRASE_LENGTH).contains(&passphrase.len())
+                    && (passphrase.len() < MAX_WPA_PASSPHRASE_LENGTH
+                        || passphrase.bytes().all(|byte| byte.is_ascii_hexdigit()))
+            }
+            JoinCredentials::Wep { index, key } => {
+                *index <= MAX_WEP_KEY_INDEX && WEP_KEY_LENGTHS.contains(&key.len())
+            }
+            JoinCredentials::Enterprise {
+                identity,
+                username,
+                password,
+                ..
+            } => {
+                identity.len() <= MAX_ENTERPRISE_IDENTITY_LENGTH
+                    && username.len() <= MAX_ENTERPRISE_CREDENTIAL_LENGTH
+                    && password.len() <= MAX_ENTERPRISE_CREDENTIAL_LENGTH
+            }
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(NetworkError::InvalidCredentials.into())
+        }
+    }
+}
+
+// Checks the SSID and credentials of a network to join before anything is sent, the same
+// way for both the blocking and async front ends.
+pub(crate) fn validate_join(ssid: &str, credentials: &JoinCredentials) -> Result<(), Error> {
+    validate_ssid(ssid)?;
+    credentials.validate()
+}
+
+fn validate_ssid(ssid: &str) -> Result<(), Error> {
+    if ssid.is_empty() || ssid.len() > MAX_SSID_LENGTH {
+        return Err(NetworkError::InvalidCredentials.into());
+    }
+    Ok(())
+}
+
 /// Base type for controlling an ESP32-WROOM NINA firmware-based WiFi board.
 #[derive(Debug)]
 pub struct Wifi<B, C> {
@@ -146,6 +427,7 @@ pub struct Wifi<B, C> {
 impl<S, C> Wifi<S, C>
 where
     S: Transfer<u8>,
+    S::Error: Debug,
     C: EspControlInterface,
 {
     /// Initialize the ESP32-WROOM WiFi device.
@@ -155,18 +437,37 @@ where
         esp32_control_pins: C,
         delay: &mut D,
     ) -> Result<Wifi<S, C>, Error> {
-        let wifi = Wifi {
+        let mut wifi = Wifi {
             protocol_handler: RefCell::new(NinaProtocolHandler {
                 bus: RefCell::new(spi),
                 control_pins: esp32_control_pins,
+                command_timeout_ms: DEFAULT_COMMAND_TIMEOUT_MS,
+                client_credentials: ClientCredentials::default(),
             }),
         };
 
-        wifi.protocol_handler.borrow_mut().init();
-        wifi.protocol_handler.borrow_mut().reset(delay);
+        wifi.reset(delay)?;
         Ok(wifi)
     }
 
+    /// Reset the ESP32-WROOM device and put it back in the same known good state as [`Wifi::init`].
+    /// Anything stored on the device is lost, so the network has to be joined again and any
+    /// client certificate and private key set again.
+    pub fn reset<D: DelayMs<u16>>(&mut self, delay: &mut D) -> Result<(), Error> {
+        let protocol_handler = self.protocol_handler.get_mut();
+
+        protocol_handler.init()?;
+        protocol_handler.reset(delay)
+    }
+
+    /// Set how long in milliseconds to wait for the ESP32-WROOM device to become ready for each
+    /// command before failing with `ProtocolError::CommunicationTimeout`. Defaults to
+    /// [`DEFAULT_COMMAND_TIMEOUT_MS`]. Only enforced when the control pins provide a time source,
+    /// e.g. by wrapping them in a [`TimeoutEspControl`](super::gpio::TimeoutEspControl).
+    pub fn set_command_timeout(&mut self, timeout_ms: u32) {
+        self.protocol_handler.get_mut().command_timeout_ms = timeout_ms;
+    }
+
     /// Retrieve the NINA firmware version contained on the connected ESP32-WROOM device (e.g. 1.7.4).
     pub fn firmware_version(&mut self) -> Result<FirmwareVersion, Error> {
         self.protocol_handler.borrow_mut().get_fw_version()
@@ -179,6 +480,146 @@ where
             .set_passphrase(ssid, passphrase)
     }
 
+    /// Join a WiFi network given an SSID and a Passphrase, then wait until it has been joined.
+    /// Gives up with `NetworkError::JoinTimeout` once at least `timeout_ms` milliseconds have
+    /// been spent waiting, or with `NetworkError::JoinFailed` carrying the
+    /// [`DisconnectReason`] reported by the device if joining fails, e.g. because of a wrong
+    /// passphrase or because no network named `ssid` was found.
+    pub fn join_and_wait<D: DelayMs<u16>>(
+        &mut self,
+        ssid: &str,
+        passphrase: &str,
+        timeout_ms: u32,
+        delay: &mut D,
+    ) -> Result<(), Error> {
+        self.join(ssid, passphrase)?;
+
+        let mut waited_ms: u32 = 0;
+        loop {
+            match self.get_connection_status()? {
+                ConnectionStatus::Connected => return Ok(()),
+                // NINA firmware reports a wrong passphrase as Disconnected and a missing
+                // network as NoActiveSsid, and doesn't retry either once the join was issued
+                ConnectionStatus::Failed
+                | ConnectionStatus::Disconnected
+                | ConnectionStatus::NoActiveSsid => {
+                    let reason = self.protocol_handler.borrow_mut().get_reason_code()?;
+                    return Err(NetworkError::JoinFailed(reason).into());
+                }
+                _ if waited_ms >= timeout_ms => return Err(NetworkError::JoinTimeout.into()),
+                _ => {
+                    delay.delay_ms(JOIN_POLL_INTERVAL_MS);
+                    waited_ms = waited_ms.saturating_add(JOIN_POLL_INTERVAL_MS.into());
+                }
+            }
+        }
+    }
+
+    /// Join a WPA2-Enterprise (802.1X) WiFi network given an SSID, the outer `identity`
+    /// (often anonymous) and the `username` and `password` to authenticate with, e.g. using
+    /// PEAP/MSCHAPv2. The authentication server's certificate is verified against `ca_cert`
+    /// (PEM encoded) when given.
+    pub fn join_enterprise(
+        &mut self,
+        ssid: &str,
+        identity: &str,
+        username: &str,
+        password: &str,
+        ca_cert: Option<&[u8]>,
+    ) -> Result<(), Error> {
+        self.connect(
+            ssid,
+            JoinCredentials::Enterprise {
+                identity,
+                username,
+                password,
+                ca_cert,
+            },
+        )
+    }
+
+    /// Join the WiFi network named `ssid` using the given [`JoinCredentials`]. The SSID and
+    /// credentials are checked before anything is sent to the ESP32-WROOM device, returning
+    /// `NetworkError::InvalidCredentials` if they can't be valid. Poll
+    /// [`Wifi::get_connection_status`] to know when the network has been joined.
+    pub fn connect(&mut self, ssid: &str, credentials: JoinCredentials) -> Result<(), Error> {
+        validate_join(ssid, &credentials)?;
+
+        let mut protocol_handler = self.protocol_handler.borrow_mut();
+        match credentials {
+            JoinCredentials::Open => protocol_handler.set_network(ssid),
+            JoinCredentials::Wpa(passphrase) => protocol_handler.set_passphrase(ssid, passphrase),
+            JoinCredentials::Wep { index, key } => protocol_handler.set_key(ssid, index, key),
+            JoinCredentials::Enterprise {
+                identity,
+                username,
+                password,
+                ca_cert,
+            } => {
+                protocol_handler.set_ent_identity(identity)?;
+                protocol_handler.set_ent_username(username)?;
+                protocol_handler.set_ent_password(password)?;
+                if let Some(ca_cert) = ca_cert {
+                    protocol_handler.set_ent_ca_cert(ca_cert)?;
+                }
+                protocol_handler.set_ent_enable()?;
+                protocol_handler.set_network(ssid)
+            }
+        }
+    }
+
+    /// Start an access point named `ssid` on WiFi `channel` that other devices can join,
+    /// protected by WPA2 when a `passphrase` is given. The SSID and passphrase are checked
+    /// before anything is sent to the ESP32-WROOM device, returning
+    /// `NetworkError::InvalidCredentials` if they can't be valid. Poll
+    /// [`Wifi::get_connection_status`] for [`ConnectionStatus::ApListening`] to know when it's up.
+    pub fn start_access_point(
+        &mut self,
+        ssid: &str,
+        passphrase: Option<&str>,
+        channel: u8,
+    ) -> Result<(), Error> {
+        validate_ssid(ssid)?;
+        // An access point is only set up with a passphrase, never with a 64 character key
+        if let Some(passphrase) = passphrase {
+            if !(MIN_WPA_PASSPHRASE_LENGTH..MAX_WPA_PASSPHRASE_LENGTH).contains(&passphrase.len()) {
+                return Err(NetworkError::InvalidCredentials.into());
+            }
+        }
+
+        let mut protocol_handler = self.protocol_handler.borrow_mut();
+        match passphrase {
+            Some(passphrase) => protocol_handler.set_ap_passphrase(ssid, passphrase, channel),
+            None => protocol_handler.set_ap_net(ssid, channel),
+        }
+    }
+
+    /// Check whether any station is connected to the access point started with
+    /// [`Wifi::start_access_point`]. NINA firmware only reports whether there are stations
+    /// connected, not how many.
+    pub fn access_point_has_stations(&mut self) -> Result<bool, Error> {
+        Ok(self.get_connection_status()? == ConnectionStatus::ApConnected)
+    }
+
+    /// Store a PEM encoded client certificate on the ESP32-WROOM device for mutual TLS.
+    /// It is presented to servers on connections using [`TransportMode::TlsBearSsl`] once a
+    /// private key has also been set with [`Wifi::set_private_key`].
+    ///
+    /// [`TransportMode::TlsBearSsl`]: super::network::TransportMode::TlsBearSsl
+    pub fn set_client_certificate(&mut self, certificate: &[u8]) -> Result<(), Error> {
+        self.protocol_handler
+            .borrow_mut()
+            .set_client_cert(certificate)
+    }
+
+    /// Store the PEM encoded private key matching the client certificate set with
+    /// [`Wifi::set_client_certificate`] on the ESP32-WROOM device for mutual TLS.
+    pub fn set_private_key(&mut self, private_key: &[u8]) -> Result<(), Error> {
+        self.protocol_handler
+            .borrow_mut()
+            .set_private_key(private_key)
+    }
+
     /// Disconnect from a previously joined WiFi network.
     pub fn leave(&mut self) -> Result<(), Error> {
         self.protocol_handler.borrow_mut().disconnect()
@@ -196,11 +637,93 @@ where
             .set_dns_config(dns1, dns2)
     }
 
+    /// Use a static IP configuration instead of DHCP, made up of the device's `local_ip`,
+    /// the network's `gateway` and its `subnet` mask.
+    pub fn set_ip_config(
+        &mut self,
+        local_ip: IpAddress,
+        gateway: IpAddress,
+        subnet: IpAddress,
+    ) -> Result<(), Error> {
+        self.protocol_handler
+            .borrow_mut()
+            .set_ip_config(local_ip, gateway, subnet)
+    }
+
+    /// Retrieve the current [`IpConfig`] of the ESP32-WROOM device, whether set with
+    /// [`Wifi::set_ip_config`] or assigned by DHCP.
+    pub fn ip_config(&mut self) -> Result<IpConfig, Error> {
+        self.protocol_handler.borrow_mut().get_ip_addr()
+    }
+
+    /// Retrieve the MAC address of the ESP32-WROOM device's WiFi interface.
+    pub fn mac_address(&mut self) -> Result<MacAddress, Error> {
+        self.protocol_handler.borrow_mut().get_mac_addr()
+    }
+
+    /// Retrieve the SSID of the currently joined WiFi network.
+    pub fn current_ssid(&mut self) -> Result<String<MAX_SSID_LENGTH>, Error> {
+        self.protocol_handler.borrow_mut().get_curr_ssid()
+    }
+
+    /// Retrieve the BSSID (MAC address of the access point) of the currently joined WiFi network.
+    pub fn current_bssid(&mut self) -> Result<MacAddress, Error> {
+        self.protocol_handler.borrow_mut().get_curr_bssid()
+    }
+
+    /// Retrieve the signal strength of the currently joined WiFi network.
+    pub fn rssi(&mut self) -> Result<Rssi, Error> {
+        self.protocol_handler.borrow_mut().get_curr_rssi()
+    }
+
+    /// Retrieve the encryption used by the currently joined WiFi network.
+    pub fn encryption_type(&mut self) -> Result<EncryptionType, Error> {
+        self.protocol_handler.borrow_mut().get_curr_enct()
+    }
+
     /// Query the DNS server(s) provided via `set_dns` for the associated IP address to the provided hostname.
     pub fn resolve(&mut self, hostname: &str) -> Result<IpAddress, Error> {
         self.protocol_handler.borrow_mut().resolve(hostname)
     }
 
+    /// Send an ICMP echo request (ping) with time to live `ttl` to the host at `ip`, returning
+    /// the round trip time in milliseconds. Fails with `NetworkError::PingTimeout` if no reply
+    /// arrives, or with `NetworkError::HostUnreachable` if the host can't be reached.
+    pub fn ping(&mut self, ip: IpAddress, ttl: u8) -> Result<u16, Error> {
+        self.protocol_handler.borrow_mut().ping(ip, ttl)
+    }
+
+    /// Resolve `hostname` and ping the resulting IP address like [`Wifi::ping`].
+    pub fn ping_hostname(&mut self, hostname: &str, ttl: u8) -> Result<u16, Error> {
+        let mut protocol_handler = self.protocol_handler.borrow_mut();
+        let ip = protocol_handler.resolve(hostname)?;
+        protocol_handler.ping(ip, ttl)
+    }
+
+    /// Set the hostname the ESP32-WROOM device reports to the network, e.g. to a DHCP server.
+    /// Call this before [`Wifi::join`] for it to take effect on the next network joined.
+    /// Returns `ProtocolError::PayloadTooLarge` without sending anything if `hostname` is longer
+    /// than 255 bytes.
+    pub fn set_hostname(&mut self, hostname: &str) -> Result<(), Error> {
+        if hostname.len() > MAX_NINA_SMALL_ARRAY_PARAM_BUFFER_LENGTH {
+            return Err(ProtocolError::PayloadTooLarge.into());
+        }
+
+        self.protocol_handler.borrow_mut().set_hostname(hostname)
+    }
+
+    /// Retrieve the current time as seconds since the Unix epoch, which the ESP32-WROOM device
+    /// syncs over SNTP once a WiFi network has been joined. Fails with
+    /// `NetworkError::TimeNotSynced` until that has happened.
+    pub fn network_time(&mut self) -> Result<u32, Error> {
+        self.protocol_handler.borrow_mut().get_time()
+    }
+
+    /// Scan for nearby WiFi networks, returning up to [`MAX_SCAN_RESULTS`] of them.
+    pub fn scan(&mut self) -> Result<Vec<ScanResult, MAX_SCAN_RESULTS>, Error> {
+        self.protocol_handler.borrow_mut().scan_networks()
+    }
+
     /// Return a reference to the `Spi` bus instance typically used when cleaning up
     /// an instance of [`Wifi`].
     pub fn destroy(self) -> S {
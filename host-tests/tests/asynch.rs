@@ -0,0 +1,379 @@
+use core::cell::Cell;
+use core::convert::Infallible;
+use core::future::{self, Future};
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use embedded_hal_1::digital::{self, InputPin, OutputPin};
+use embedded_hal_1::spi::{self, Operation};
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::spi::SpiDevice;
+
+use esp32_wroom_rp::asynch::tcp_client::TcpClient;
+use esp32_wroom_rp::asynch::wifi::Wifi;
+use esp32_wroom_rp::gpio::EspControlPins;
+use esp32_wroom_rp::network::{IpAddress, NetworkError, TransportMode};
+use esp32_wroom_rp::Error;
+
+// Records every byte written to it and answers reads from a queue of response bytes,
+// so that expectations don't need to interleave what's sent with what's received.
+#[derive(Default)]
+struct SpiDeviceMock {
+    written: Vec<u8>,
+    responses: VecDeque<u8>,
+}
+
+impl SpiDeviceMock {
+    fn new(responses: &[u8]) -> Self {
+        Self {
+            written: Vec::new(),
+            responses: responses.iter().copied().collect(),
+        }
+    }
+
+    fn read(&mut self, words: &mut [u8]) {
+        for word in words.iter_mut() {
+            *word = self
+                .responses
+                .pop_front()
+                .expect("Read more bytes than the mocked responses hold");
+        }
+    }
+}
+
+impl spi::ErrorType for SpiDeviceMock {
+    type Error = Infallible;
+}
+
+impl SpiDevice for SpiDeviceMock {
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Infallible> {
+        for operation in operations.iter_mut() {
+            match operation {
+                Operation::Write(words) => self.written.extend_from_slice(words),
+                Operation::Read(words) | Operation::TransferInPlace(words) => self.read(words),
+                Operation::Transfer(read, write) => {
+                    self.written.extend_from_slice(write);
+                    self.read(read);
+                }
+                Operation::DelayNs(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+// Never finishes a transaction, like an SPI device that's still busy when a call is dropped
+struct PendingSpiDevice;
+
+impl spi::ErrorType for PendingSpiDevice {
+    type Error = Infallible;
+}
+
+impl SpiDevice for PendingSpiDevice {
+    async fn transaction(
+        &mut self,
+        _operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Infallible> {
+        future::pending().await
+    }
+}
+
+// A chip select pin that records whether it's high, i.e. the ESP32 target is deselected
+struct ChipSelectMock(Rc<Cell<bool>>);
+
+impl digital::ErrorType for ChipSelectMock {
+    type Error = Infallible;
+}
+
+impl OutputPin for ChipSelectMock {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.0.set(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.0.set(true);
+        Ok(())
+    }
+}
+
+// A pin that is always ready, standing in for all of the control pins
+struct PinMock;
+
+impl digital::ErrorType for PinMock {
+    type Error = Infallible;
+}
+
+impl OutputPin for PinMock {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+impl InputPin for PinMock {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        Ok(false)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        Ok(true)
+    }
+}
+
+impl Wait for PinMock {
+    async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+struct DelayMock;
+
+impl DelayNs for DelayMock {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
+
+// Adds up the nanoseconds it's asked to wait
+#[derive(Default)]
+struct DelayCounter(u64);
+
+impl DelayNs for DelayCounter {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.0 += ns as u64;
+    }
+}
+
+type PinsMock = EspControlPins<PinMock, PinMock, PinMock, PinMock>;
+
+fn pins() -> PinsMock {
+    EspControlPins {
+        cs: PinMock,
+        gpio0: PinMock,
+        resetn: PinMock,
+        ack: PinMock,
+    }
+}
+
+// None of the mocks ever return Pending, so polling once more is all it takes
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+fn init(responses: &[u8]) -> Wifi<SpiDeviceMock, PinsMock> {
+    block_on(Wifi::init(
+        SpiDeviceMock::new(responses),
+        pins(),
+        &mut DelayMock,
+    ))
+    .unwrap()
+}
+
+// Encodes a command the way NINA firmware expects it, each param with a 1 byte length
+fn command(cmd: u8, params: &[&[u8]]) -> Vec<u8> {
+    let mut bytes = vec![0xe0, cmd, params.len() as u8];
+    for param in params {
+        bytes.push(param.len() as u8);
+        bytes.extend_from_slice(param);
+    }
+    bytes.push(0xee);
+    while bytes.len() % 4 != 0 {
+        bytes.push(0xff);
+    }
+    bytes
+}
+
+// Encodes a response the way NINA firmware sends it, each param with a 1 byte length
+fn response(cmd: u8, params: &[&[u8]]) -> Vec<u8> {
+    let mut bytes = vec![0xe0, cmd | 0x80, params.len() as u8];
+    for param in params {
+        bytes.push(param.len() as u8);
+        bytes.extend_from_slice(param);
+    }
+    bytes.push(0xee);
+    bytes
+}
+
+#[test]
+fn join_sends_ssid_and_passphrase() {
+    let mut wifi = init(&response(0x11, &[&[0x1]]));
+
+    block_on(wifi.join("FFFF", "password")).unwrap();
+
+    assert_eq!(
+        wifi.destroy().written,
+        command(0x11, &[b"FFFF", b"password"])
+    );
+}
+
+#[test]
+fn join_with_too_short_passphrase_sends_nothing() {
+    let mut wifi = init(&[]);
+
+    assert_eq!(
+        block_on(wifi.join("FFFF", "short")).unwrap_err(),
+        Error::Network(NetworkError::InvalidCredentials)
+    );
+
+    assert!(wifi.destroy().written.is_empty());
+}
+
+#[test]
+fn resolve_returns_ip_address_of_hostname() {
+    let mut responses = response(0x34, &[&[0x1]]);
+    responses.append(&mut response(0x35, &[&[0x40, 0x40, 0x40, 0x40]]));
+    let mut wifi = init(&responses);
+
+    assert_eq!(
+        block_on(wifi.resolve("FFFF")).unwrap(),
+        [0x40, 0x40, 0x40, 0x40]
+    );
+
+    let mut expected = command(0x34, &[b"FFFF"]);
+    expected.append(&mut command(0x35, &[]));
+    assert_eq!(wifi.destroy().written, expected);
+}
+
+#[test]
+fn tcp_connection_sends_and_receives_data() {
+    let mut responses = response(0x3f, &[&[0x0]]); // GetSocket
+    responses.append(&mut response(0x2d, &[&[0x1]])); // StartClientTcp
+    responses.append(&mut response(0x2f, &[&[0x4]])); // ConnectionState::Established
+    responses.append(&mut response(0x44, &[&5u16.to_le_bytes()])); // SendDataTcp
+    responses.append(&mut response(0x2a, &[&[0x1]])); // DataSentTcp
+    responses.append(&mut response(0x2b, &[&5u16.to_le_bytes()])); // AvailDataTcp
+    responses.append(&mut vec![0xe0, 0xc5, 0x1, 0x0, 0x5]); // GetDatabufTcp, 2 byte length
+    responses.extend_from_slice(b"world");
+    responses.push(0xee);
+    responses.append(&mut response(0x2b, &[&0u16.to_le_bytes()])); // AvailDataTcp
+    responses.append(&mut response(0x2e, &[&[0x1]])); // StopClientTcp
+    let mut wifi = init(&responses);
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    block_on(async {
+        let mut connection = TcpClient::build(&mut wifi)
+            .open(ip_address, 0x1111, TransportMode::Tcp, &mut DelayMock)
+            .await
+            .unwrap();
+
+        connection.send_all(b"hello", &mut DelayMock).await.unwrap();
+
+        let mut buffer = [0u8; 16];
+        assert_eq!(connection.receive(&mut buffer).await.unwrap(), 5);
+        assert_eq!(&buffer[..5], b"world");
+
+        connection.close().await.unwrap();
+    });
+
+    let written = wifi.destroy().written;
+    // SendDataTcp is encoded with 2 byte param lengths
+    let mut send_data_tcp = vec![0xe0, 0x44, 0x2, 0x0, 0x1, 0x0, 0x0, 0x5];
+    send_data_tcp.extend_from_slice(b"hello");
+    send_data_tcp.append(&mut vec![0xee, 0xff, 0xff]);
+    assert!(written
+        .windows(send_data_tcp.len())
+        .any(|window| window == send_data_tcp.as_slice()));
+}
+
+#[test]
+fn tcp_connection_send_waits_between_checks_until_esp32_confirms_data_was_sent() {
+    let mut responses = response(0x3f, &[&[0x0]]); // GetSocket
+    responses.append(&mut response(0x2d, &[&[0x1]])); // StartClientTcp
+    responses.append(&mut response(0x2f, &[&[0x4]])); // ConnectionState::Established
+    responses.append(&mut response(0x44, &[&5u16.to_le_bytes()])); // SendDataTcp
+    responses.append(&mut response(0x2a, &[&[0x0]])); // DataSentTcp
+    responses.append(&mut response(0x2a, &[&[0x0]]));
+    responses.append(&mut response(0x2a, &[&[0x1]]));
+    responses.append(&mut response(0x2e, &[&[0x1]])); // StopClientTcp
+    let mut wifi = init(&responses);
+
+    let mut delay = DelayCounter::default();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    block_on(async {
+        let mut connection = TcpClient::build(&mut wifi)
+            .open(ip_address, 0x1111, TransportMode::Tcp, &mut DelayMock)
+            .await
+            .unwrap();
+
+        assert_eq!(connection.send(b"hello", &mut delay).await.unwrap(), 5);
+
+        connection.close().await.unwrap();
+    });
+
+    // 100 ms before each check after the first
+    assert_eq!(delay.0, 2 * 100_000_000);
+}
+
+#[test]
+fn tls_bearssl_connection_returns_client_credentials_not_set_error() {
+    let mut wifi = init(&response(0x3f, &[&[0x0]])); // GetSocket
+
+    let result = block_on(TcpClient::build(&mut wifi).open_hostname(
+        "FFFF",
+        0x1111,
+        TransportMode::TlsBearSsl,
+        &mut DelayMock,
+    ));
+
+    assert_eq!(
+        result.err().unwrap(),
+        Error::Network(NetworkError::ClientCredentialsNotSet)
+    );
+
+    // Only the socket was requested, the client was never started
+    assert_eq!(wifi.destroy().written, command(0x3f, &[]));
+}
+
+#[test]
+fn dropping_a_call_mid_command_deselects_the_esp32_target() {
+    let deselected = Rc::new(Cell::new(false));
+    let pins = EspControlPins {
+        cs: ChipSelectMock(deselected.clone()),
+        gpio0: PinMock,
+        resetn: PinMock,
+        ack: PinMock,
+    };
+    let mut wifi = block_on(Wifi::init(PendingSpiDevice, pins, &mut DelayMock)).unwrap();
+
+    {
+        let mut join = pin!(wifi.join("FFFF", "password"));
+        let mut context = Context::from_waker(Waker::noop());
+
+        assert!(join.as_mut().poll(&mut context).is_pending());
+        assert!(!deselected.get());
+    }
+
+    assert!(deselected.get());
+}
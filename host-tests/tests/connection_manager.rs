@@ -0,0 +1,222 @@
+use embedded_hal_mock::delay::MockNoop;
+use embedded_hal_mock::spi;
+
+use esp32_wroom_rp::connection_manager::{ConnectionEvent, ConnectionManager};
+use esp32_wroom_rp::network::{NetworkError, TransportMode};
+use esp32_wroom_rp::tcp_client::{Connect, TcpClient};
+use esp32_wroom_rp::wifi::{JoinCredentials, Wifi};
+use esp32_wroom_rp::Error;
+
+pub mod support;
+
+use support::*;
+
+fn mock_set_net() -> Vec<spi::Transaction> {
+    let set_net_command = 0x10;
+
+    let mut expectations = mock_command(set_net_command, 0x1);
+
+    expectations.append(&mut mock_single_byte_size_params(4, 0x46)); // SSID is "FFFF"
+    expectations.append(&mut mock_end_byte());
+    expectations.append(&mut mock_padding(3));
+    expectations.append(&mut mock_receive(set_net_command, 0x1, &[0x1]));
+
+    expectations
+}
+
+#[test]
+fn poll_joins_network_and_rejoins_after_connection_is_lost() {
+    let mut expectations = mock_set_net();
+
+    expectations.append(&mut mock_get_conn_status(0x0)); // Idle
+    expectations.append(&mut mock_get_conn_status(0x3)); // Connected
+    expectations.append(&mut mock_get_conn_status(0x5)); // Lost
+    expectations.append(&mut mock_set_net());
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+    let mut manager = ConnectionManager::new(wifi, "FFFF", JoinCredentials::Open);
+
+    assert_eq!(manager.poll(0, &mut delay).unwrap(), None);
+    assert_eq!(manager.poll(100, &mut delay).unwrap(), None);
+    assert_eq!(
+        manager.poll(200, &mut delay).unwrap(),
+        Some(ConnectionEvent::Connected)
+    );
+    assert!(manager.is_connected());
+    assert_eq!(
+        manager.poll(300, &mut delay).unwrap(),
+        Some(ConnectionEvent::Disconnected)
+    );
+    assert!(!manager.is_connected());
+    assert_eq!(manager.poll(400, &mut delay).unwrap(), None);
+
+    manager.release().destroy().done();
+}
+
+#[test]
+fn poll_backs_off_exponentially_between_failed_joins() {
+    let mut expectations = mock_set_net();
+
+    expectations.append(&mut mock_get_conn_status(0x4)); // Failed
+    expectations.append(&mut mock_set_net());
+    expectations.append(&mut mock_get_conn_status(0x4));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+    let mut manager = ConnectionManager::new(wifi, "FFFF", JoinCredentials::Open);
+    manager.set_backoff(1000, 1500);
+
+    assert_eq!(manager.poll(0, &mut delay).unwrap(), None);
+    assert_eq!(
+        manager.poll(10, &mut delay).unwrap(),
+        Some(ConnectionEvent::JoinFailed {
+            attempts: 1,
+            retry_in_ms: 1000
+        })
+    );
+    // Still backing off, so nothing is sent
+    assert_eq!(manager.poll(500, &mut delay).unwrap(), None);
+    assert_eq!(manager.poll(1010, &mut delay).unwrap(), None);
+    assert_eq!(
+        manager.poll(1020, &mut delay).unwrap(),
+        Some(ConnectionEvent::JoinFailed {
+            attempts: 2,
+            retry_in_ms: 1500
+        })
+    );
+
+    manager.release().destroy().done();
+}
+
+#[test]
+fn poll_resets_esp32_after_too_many_failed_joins() {
+    let mut expectations = mock_set_net();
+
+    expectations.append(&mut mock_get_conn_status(0x0)); // Idle
+    expectations.append(&mut mock_set_net());
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+    let mut manager = ConnectionManager::new(wifi, "FFFF", JoinCredentials::Open);
+    manager.set_join_timeout(100);
+    manager.set_max_failures(1);
+
+    assert_eq!(manager.poll(0, &mut delay).unwrap(), None);
+    assert_eq!(
+        manager.poll(100, &mut delay).unwrap(),
+        Some(ConnectionEvent::Reset)
+    );
+    assert_eq!(manager.poll(200, &mut delay).unwrap(), None);
+
+    manager.release().destroy().done();
+}
+
+#[test]
+fn poll_counts_failing_to_talk_to_esp32_as_failed_joins_and_resets_it() {
+    let spi = FailingSpiMock {};
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+    let mut manager = ConnectionManager::new(wifi, "FFFF", JoinCredentials::Open);
+    manager.set_backoff(1000, 1000);
+    manager.set_max_failures(2);
+
+    assert_eq!(
+        manager.poll(0, &mut delay).unwrap(),
+        Some(ConnectionEvent::JoinFailed {
+            attempts: 1,
+            retry_in_ms: 1000
+        })
+    );
+    assert_eq!(manager.poll(500, &mut delay).unwrap(), None);
+    assert_eq!(
+        manager.poll(1000, &mut delay).unwrap(),
+        Some(ConnectionEvent::Reset)
+    );
+}
+
+#[test]
+fn poll_with_invalid_credentials_returns_invalid_credentials_error() {
+    let spi = spi::Mock::new(&[]);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+    let mut manager = ConnectionManager::new(wifi, "FFFF", JoinCredentials::Wpa("short"));
+
+    assert_eq!(
+        manager.poll(0, &mut delay).unwrap_err(),
+        Error::Network(NetworkError::InvalidCredentials)
+    );
+
+    manager.release().destroy().done();
+}
+
+#[test]
+fn poll_forgets_client_credentials_when_resetting_esp32() {
+    let certificate = [0x43; 1100];
+    let private_key = [0x4b; 1600];
+
+    let mut expectations = mock_set_tls_credential(0x40, &certificate);
+
+    expectations.append(&mut mock_set_tls_credential(0x41, &private_key));
+    expectations.append(&mut mock_set_net());
+    expectations.append(&mut mock_get_conn_status(0x4)); // Failed
+    expectations.append(&mut mock_get_socket(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    wifi.set_client_certificate(&certificate).unwrap();
+    wifi.set_private_key(&private_key).unwrap();
+
+    let mut manager = ConnectionManager::new(wifi, "FFFF", JoinCredentials::Open);
+    manager.set_max_failures(1);
+
+    assert_eq!(manager.poll(0, &mut delay).unwrap(), None);
+    assert_eq!(
+        manager.poll(100, &mut delay).unwrap(),
+        Some(ConnectionEvent::Reset)
+    );
+
+    // The ESP32 target lost the credentials on reset, so they must be set again
+    let result = TcpClient::build(manager.wifi()).open(
+        "FFFF",
+        0x1111,
+        TransportMode::TlsBearSsl,
+        &mut delay,
+    );
+
+    assert_eq!(
+        result.err().unwrap(),
+        Error::Network(NetworkError::ClientCredentialsNotSet)
+    );
+
+    manager.release().destroy().done();
+}
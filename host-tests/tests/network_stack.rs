@@ -0,0 +1,202 @@
+use core::net::{Ipv4Addr, SocketAddr};
+
+use embedded_hal_mock::delay::MockNoop;
+use embedded_hal_mock::spi;
+
+use embedded_nal::{nb, AddrType, Dns, TcpClientStack, TcpError, TcpErrorKind, UdpClientStack};
+
+use esp32_wroom_rp::network::NetworkError;
+use esp32_wroom_rp::network_stack::NetworkStack;
+use esp32_wroom_rp::wifi::Wifi;
+use esp32_wroom_rp::Error;
+
+pub mod support;
+
+use support::*;
+
+fn mock_start_client_tcp() -> Vec<spi::Transaction> {
+    let start_client_tcp_command = 0x2d;
+
+    let mut expectations = mock_command(start_client_tcp_command, 0x4);
+
+    expectations.append(&mut mock_single_byte_size_params(4, 0x40)); // Send fake IP Address
+    expectations.append(&mut mock_single_byte_size_params(2, 0x11)); // Send fake Port
+    expectations.append(&mut mock_single_byte_size_params(1, 0x0)); // Send fake Socket
+    expectations.append(&mut mock_single_byte_size_params(1, 0x0)); // Send TCP Transport Mode
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_receive(start_client_tcp_command, 0x1, &[0x1]));
+
+    expectations
+}
+
+fn remote_address() -> SocketAddr {
+    SocketAddr::new(Ipv4Addr::new(0x40, 0x40, 0x40, 0x40).into(), 0x1111)
+}
+
+#[test]
+fn tcp_stack_connects_sends_and_closes_socket() {
+    let mut expectations = mock_get_socket(0x0);
+
+    expectations.append(&mut mock_start_client_tcp());
+    expectations.append(&mut mock_get_client_state_tcp(0x4)); // ConnectionState::Established
+    expectations.append(&mut mock_send_data_tcp(b"hello", 5));
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+    let mut stack = NetworkStack::build(&mut wifi);
+
+    let mut socket = TcpClientStack::socket(&mut stack).unwrap();
+    TcpClientStack::connect(&mut stack, &mut socket, remote_address()).unwrap();
+    assert_eq!(
+        TcpClientStack::send(&mut stack, &mut socket, b"hello").unwrap(),
+        5
+    );
+    TcpClientStack::close(&mut stack, socket).unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_stack_receive_would_block_until_connection_is_closed() {
+    let mut expectations = mock_get_socket(0x0);
+
+    expectations.append(&mut mock_avail_data_tcp(0x0, 0));
+    expectations.append(&mut mock_get_client_state_tcp(0x4)); // ConnectionState::Established
+    expectations.append(&mut mock_avail_data_tcp(0x0, 0));
+    expectations.append(&mut mock_get_client_state_tcp(0x0)); // ConnectionState::Closed
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+    let mut stack = NetworkStack::build(&mut wifi);
+
+    let mut socket = TcpClientStack::socket(&mut stack).unwrap();
+    let mut buffer = [0u8; 16];
+
+    assert_eq!(
+        TcpClientStack::receive(&mut stack, &mut socket, &mut buffer).unwrap_err(),
+        nb::Error::WouldBlock
+    );
+
+    match TcpClientStack::receive(&mut stack, &mut socket, &mut buffer).unwrap_err() {
+        nb::Error::Other(error) => {
+            assert_eq!(error, Error::Network(NetworkError::ConnectionClosed));
+            assert_eq!(error.kind(), TcpErrorKind::PipeClosed);
+        }
+        nb::Error::WouldBlock => panic!("Expected the connection to be reported as closed"),
+    }
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_stack_receive_would_block_when_esp32_returns_no_data_despite_reporting_some() {
+    let mut expectations = mock_get_socket(0x0);
+
+    expectations.append(&mut mock_avail_data_tcp(0x0, 5));
+    expectations.append(&mut mock_get_databuf_tcp(0x0, 5, &[]));
+    expectations.append(&mut mock_avail_data_tcp(0x0, 5));
+    expectations.append(&mut mock_get_databuf_tcp(0x0, 5, b"hello"));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+    let mut stack = NetworkStack::build(&mut wifi);
+
+    let mut socket = TcpClientStack::socket(&mut stack).unwrap();
+    let mut buffer = [0u8; 5];
+
+    assert_eq!(
+        TcpClientStack::receive(&mut stack, &mut socket, &mut buffer).unwrap_err(),
+        nb::Error::WouldBlock
+    );
+    assert_eq!(
+        TcpClientStack::receive(&mut stack, &mut socket, &mut buffer).unwrap(),
+        5
+    );
+    assert_eq!(&buffer, b"hello");
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_stack_socket_returns_no_socket_available_error_when_esp32_has_none_left() {
+    // NINA firmware replies with 255 once all of its sockets are in use
+    let expectations = mock_get_socket(0xff);
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+    let mut stack = NetworkStack::build(&mut wifi);
+
+    assert_eq!(
+        TcpClientStack::socket(&mut stack).unwrap_err(),
+        Error::Network(NetworkError::NoSocketAvailable)
+    );
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn udp_stack_send_before_connect_returns_send_failed_error() {
+    let expectations = mock_get_socket(0x0);
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+    let mut stack = NetworkStack::build(&mut wifi);
+
+    let mut socket = UdpClientStack::socket(&mut stack).unwrap();
+
+    assert_eq!(
+        UdpClientStack::send(&mut stack, &mut socket, b"hello").unwrap_err(),
+        nb::Error::Other(Error::Network(NetworkError::SendFailed))
+    );
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn dns_lookup_of_ipv6_address_returns_unsupported_error() {
+    let spi = spi::Mock::new(&[]);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+    let mut stack = NetworkStack::build(&mut wifi);
+
+    assert_eq!(
+        stack
+            .get_host_by_name("example.com", AddrType::IPv6)
+            .unwrap_err(),
+        nb::Error::Other(Error::Network(NetworkError::Unsupported))
+    );
+
+    wifi.destroy().done();
+}
@@ -21,11 +21,9 @@ fn too_many_parameters_error() {
         spi::Transaction::transfer(vec![0xff], vec![0xe0]),
         // read command byte | reply byte
         spi::Transaction::transfer(vec![0xff], vec![command_or_reply_byte(command)]),
-        // read number of params to receive
-        spi::Transaction::transfer(vec![0xff], vec![0x1]),
-        // test relies on max number of parameters being 8. This will probably change
-        // as we understand more.
-        spi::Transaction::transfer(vec![0xff], vec![0x9]),
+        // read number of params to receive, test relies on max number of
+        // parameters being 10.
+        spi::Transaction::transfer(vec![0xff], vec![0xb]),
     ];
 
     expectations.append(&mut too_man_parameters_expectations);
@@ -86,6 +84,52 @@ fn invalid_number_of_parameters_error() {
     wifi.destroy().done();
 }
 
+#[test]
+fn missing_end_byte_error() {
+    let command = 0x37;
+    let number_of_params = 0x0;
+    let mut expectations = mock_command(command, number_of_params);
+
+    expectations.append(&mut mock_end_byte());
+
+    let mut missing_end_byte_expectations = vec![
+        // wait_response_cmd()
+        // read start command
+        spi::Transaction::transfer(vec![0xff], vec![0xe0]),
+        // read command byte | reply byte
+        spi::Transaction::transfer(vec![0xff], vec![command_or_reply_byte(command)]),
+        // read number of params to receive
+        spi::Transaction::transfer(vec![0xff], vec![0x1]),
+        // read 1-byte param length
+        spi::Transaction::transfer(vec![0xff], vec![0x5]),
+        spi::Transaction::transfer(vec![0xff], vec![0x31]),
+        spi::Transaction::transfer(vec![0xff], vec![0x2e]),
+        spi::Transaction::transfer(vec![0xff], vec![0x37]),
+        spi::Transaction::transfer(vec![0xff], vec![0x2e]),
+        spi::Transaction::transfer(vec![0xff], vec![0x34]),
+        // read end byte (should be 0xee)
+        spi::Transaction::transfer(vec![0xff], vec![0x0]),
+    ];
+
+    expectations.append(&mut missing_end_byte_expectations);
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+    let f = wifi.firmware_version();
+
+    assert_eq!(
+        f.unwrap_err(),
+        esp32_wroom_rp::Error::Protocol(esp32_wroom_rp::protocol::ProtocolError::MissingEndByte)
+    );
+
+    wifi.destroy().done();
+}
+
 #[test]
 fn invalid_command_induces_invalid_command_error() {
     let command = 0x37;
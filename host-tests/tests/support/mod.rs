@@ -1,29 +1,158 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use embedded_hal::blocking::delay::{DelayMs, DelayUs};
+use embedded_hal::blocking::spi::Transfer;
 use embedded_hal_mock::spi;
 use esp32_wroom_rp::gpio::EspControlInterface;
+use esp32_wroom_rp::{BusError, Error, HalError};
 
 pub(crate) struct EspControlMock {}
 
 impl EspControlInterface for EspControlMock {
-    fn init(&mut self) {}
+    fn init(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn reset<D>(&mut self, _delay: &mut D) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_esp_ack(&self) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    fn wait_for_esp_select(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn wait_for_esp_ack(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn wait_for_esp_ready(&self) -> Result<(), Error> {
+        Ok(())
+    }
 
-    fn reset<D>(&mut self, _delay: &mut D) {}
+    fn esp_select(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn esp_deselect(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_esp_ready(&self) -> Result<bool, Error> {
+        Ok(true)
+    }
+}
+
+// Simulates an ESP32 target whose ready/ack control pin can't be read
+pub struct FailingEspControlMock {}
+
+impl EspControlInterface for FailingEspControlMock {
+    fn init(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn reset<D>(&mut self, _delay: &mut D) -> Result<(), Error> {
+        Ok(())
+    }
 
-    fn get_esp_ack(&self) -> bool {
-        true
+    fn get_esp_ack(&self) -> Result<bool, Error> {
+        Err(BusError::ControlPin(HalError::new(&"ack pin unreadable")).into())
     }
 
-    fn wait_for_esp_select(&mut self) {}
+    fn wait_for_esp_select(&mut self) -> Result<(), Error> {
+        self.wait_for_esp_ready()
+    }
 
-    fn wait_for_esp_ack(&self) {}
+    fn wait_for_esp_ack(&self) -> Result<(), Error> {
+        self.get_esp_ack().map(|_| ())
+    }
 
-    fn wait_for_esp_ready(&self) {}
+    fn wait_for_esp_ready(&self) -> Result<(), Error> {
+        self.get_esp_ready().map(|_| ())
+    }
 
-    fn esp_select(&mut self) {}
+    fn esp_select(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
 
-    fn esp_deselect(&mut self) {}
+    fn esp_deselect(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
 
-    fn get_esp_ready(&self) -> bool {
-        true
+    fn get_esp_ready(&self) -> Result<bool, Error> {
+        Err(BusError::ControlPin(HalError::new(&"ack pin unreadable")).into())
+    }
+}
+
+// Simulates an unresponsive ESP32 target that never signals it is ready
+pub struct UnresponsiveEspControlMock {}
+
+impl EspControlInterface for UnresponsiveEspControlMock {
+    fn init(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn reset<D>(&mut self, _delay: &mut D) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_esp_ack(&self) -> Result<bool, Error> {
+        Ok(false)
+    }
+
+    fn wait_for_esp_select(&mut self) -> Result<(), Error> {
+        unreachable!("an unresponsive ESP32 target would block forever")
+    }
+
+    fn wait_for_esp_ack(&self) -> Result<(), Error> {
+        unreachable!("an unresponsive ESP32 target would block forever")
+    }
+
+    fn wait_for_esp_ready(&self) -> Result<(), Error> {
+        unreachable!("an unresponsive ESP32 target would block forever")
+    }
+
+    fn esp_select(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn esp_deselect(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_esp_ready(&self) -> Result<bool, Error> {
+        Ok(false)
+    }
+}
+
+// Adds up the microseconds it's asked to wait, which tests can read back through a clone
+#[derive(Clone, Default)]
+pub struct DelayCounter(pub Rc<Cell<u32>>);
+
+impl DelayUs<u16> for DelayCounter {
+    fn delay_us(&mut self, us: u16) {
+        self.0.set(self.0.get() + us as u32);
+    }
+}
+
+impl DelayMs<u16> for DelayCounter {
+    fn delay_ms(&mut self, ms: u16) {
+        self.0.set(self.0.get() + ms as u32 * 1_000);
+    }
+}
+
+// Simulates a SPI bus where every transfer fails
+pub struct FailingSpiMock {}
+
+impl Transfer<u8> for FailingSpiMock {
+    type Error = ();
+
+    fn transfer<'w>(&mut self, _words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        Err(())
     }
 }
 
@@ -55,6 +184,20 @@ pub fn mock_single_byte_size_params(
     expectations
 }
 
+pub fn mock_double_byte_size_params(values: &[u8]) -> Vec<spi::Transaction> {
+    let length = values.len() as u16;
+    let mut expectations = vec![
+        spi::Transaction::transfer(vec![(length >> 8) as u8], vec![0x0]),
+        spi::Transaction::transfer(vec![(length & 0xff) as u8], vec![0x0]),
+    ];
+
+    for byte in values.iter().cloned() {
+        expectations.push(spi::Transaction::transfer(vec![byte], vec![0x0]));
+    }
+
+    expectations
+}
+
 pub fn mock_padding(number_of_padding_bytes: u8) -> Vec<spi::Transaction> {
     let mut expectations = Vec::new();
     for _ in 0..number_of_padding_bytes {
@@ -76,17 +219,30 @@ pub fn mock_receive(
     number_of_params_to_receive: u8,
     values_to_receive: &[u8],
 ) -> Vec<spi::Transaction> {
-    let mut buffer = vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x21];
+    let mut expectations = vec![
+        // wait_response_cmd()
+        // read start command
+        spi::Transaction::transfer(vec![0xff], vec![0xe0]),
+        // read command byte | reply byte
+        spi::Transaction::transfer(vec![0xff], vec![command_or_reply_byte(command_byte)]),
+        // read number of params to receive
+        spi::Transaction::transfer(vec![0xff], vec![number_of_params_to_receive]),
+        // read 1-byte param length
+        spi::Transaction::transfer(vec![0xff], vec![values_to_receive.len() as u8]),
+    ];
 
-    let length_of_values = if values_to_receive.len() > 0 {
-        values_to_receive.len() - 1
-    } else {
-        0
-    };
+    for byte in values_to_receive.iter().cloned() {
+        expectations.push(spi::Transaction::transfer(vec![0xff], vec![byte]));
+    }
 
-    // replace buffer values with values from values_to_receive
-    buffer.splice(0..length_of_values, values_to_receive.iter().cloned());
+    // read end byte
+    expectations.push(spi::Transaction::transfer(vec![0xff], vec![0xee]));
+
+    expectations
+}
 
+pub fn mock_receive_data16(command_byte: u8, values_to_receive: &[u8]) -> Vec<spi::Transaction> {
+    let length = values_to_receive.len() as u16;
     let mut expectations = vec![
         // wait_response_cmd()
         // read start command
@@ -94,19 +250,47 @@ pub fn mock_receive(
         // read command byte | reply byte
         spi::Transaction::transfer(vec![0xff], vec![command_or_reply_byte(command_byte)]),
         // read number of params to receive
-        spi::Transaction::transfer(vec![0xff], vec![number_of_params_to_receive]),
-        // test relies on max number of parameters being 8. This will probably change
-        // as we understand more.
-        spi::Transaction::transfer(vec![0xff], vec![0x8]),
+        spi::Transaction::transfer(vec![0xff], vec![0x1]),
+        // read 2-byte param length
+        spi::Transaction::transfer(vec![0xff], vec![(length >> 8) as u8]),
+        spi::Transaction::transfer(vec![0xff], vec![(length & 0xff) as u8]),
+    ];
+
+    for byte in values_to_receive.iter().cloned() {
+        expectations.push(spi::Transaction::transfer(vec![0xff], vec![byte]));
+    }
+
+    // read end byte
+    expectations.push(spi::Transaction::transfer(vec![0xff], vec![0xee]));
+
+    expectations
+}
+
+pub fn mock_receive_params(command_byte: u8, params_to_receive: &[&[u8]]) -> Vec<spi::Transaction> {
+    let mut expectations = vec![
+        // wait_response_cmd()
+        // read start command
+        spi::Transaction::transfer(vec![0xff], vec![0xe0]),
+        // read command byte | reply byte
+        spi::Transaction::transfer(vec![0xff], vec![command_or_reply_byte(command_byte)]),
+        // read number of params to receive
+        spi::Transaction::transfer(vec![0xff], vec![params_to_receive.len() as u8]),
     ];
 
-    for byte in buffer.iter().cloned() {
-        expectations.append(&mut vec![spi::Transaction::transfer(
+    for param in params_to_receive.iter() {
+        // read 1-byte param length
+        expectations.push(spi::Transaction::transfer(
             vec![0xff],
-            vec![byte],
-        )]);
-        // expectations.push(spi::Transaction::transfer(vec![0xff], vec![byte]));
+            vec![param.len() as u8],
+        ));
+        for byte in param.iter().cloned() {
+            expectations.push(spi::Transaction::transfer(vec![0xff], vec![byte]));
+        }
     }
+
+    // read end byte
+    expectations.push(spi::Transaction::transfer(vec![0xff], vec![0xee]));
+
     expectations
 }
 
@@ -115,5 +299,172 @@ pub fn command_or_reply_byte(command: u8) -> u8 {
 }
 
 pub fn command_and_reply_byte(command: u8) -> u8 {
-    (command as u8) & !(0x80 as u8)
+    command & !0x80_u8
+}
+
+pub fn mock_get_socket(socket: u8) -> Vec<spi::Transaction> {
+    let get_socket_command = 0x3f;
+
+    let mut expectations = mock_command(get_socket_command, 0x0);
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_receive(get_socket_command, 0x1, &[socket]));
+
+    expectations
+}
+
+pub fn mock_stop_client_tcp(socket: u8) -> Vec<spi::Transaction> {
+    let stop_client_tcp_command = 0x2e;
+
+    let mut expectations = mock_command(stop_client_tcp_command, 0x1);
+
+    expectations.append(&mut mock_single_byte_size_params(1, socket));
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(2));
+
+    expectations.append(&mut mock_receive(stop_client_tcp_command, 0x1, &[0x1]));
+
+    expectations
+}
+
+pub fn mock_avail_data_tcp(socket: u8, available: u16) -> Vec<spi::Transaction> {
+    let avail_data_tcp_command = 0x2b;
+
+    let mut expectations = mock_command(avail_data_tcp_command, 0x1);
+
+    expectations.append(&mut mock_single_byte_size_params(1, socket));
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(2));
+
+    expectations.append(&mut mock_receive(
+        avail_data_tcp_command,
+        0x1,
+        &available.to_le_bytes(),
+    ));
+
+    expectations
+}
+
+pub fn mock_get_databuf_tcp(
+    socket: u8,
+    requested_length: u16,
+    values: &[u8],
+) -> Vec<spi::Transaction> {
+    let get_databuf_tcp_command = 0x45;
+
+    let mut expectations = mock_command(get_databuf_tcp_command, 0x2);
+
+    expectations.append(&mut mock_double_byte_size_params(&[socket]));
+    expectations.append(&mut mock_double_byte_size_params(
+        &requested_length.to_le_bytes(),
+    ));
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(1));
+
+    expectations.append(&mut mock_receive_data16(get_databuf_tcp_command, values));
+
+    expectations
+}
+
+pub fn mock_set_tls_credential(command: u8, values: &[u8]) -> Vec<spi::Transaction> {
+    let mut expectations = mock_command(command, 0x1);
+
+    expectations.append(&mut mock_double_byte_size_params(values));
+
+    expectations.append(&mut mock_end_byte());
+
+    let command_size = 4 + 2 + values.len();
+    expectations.append(&mut mock_padding(((4 - command_size % 4) % 4) as u8));
+
+    expectations.append(&mut mock_receive(command, 0x1, &[0x1]));
+
+    expectations
+}
+
+pub fn mock_get_conn_status(status: u8) -> Vec<spi::Transaction> {
+    let get_conn_status_command = 0x20;
+
+    let mut expectations = mock_command(get_conn_status_command, 0x0);
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_receive(get_conn_status_command, 0x1, &[status]));
+
+    expectations
+}
+
+pub fn mock_get_client_state_tcp(state: u8) -> Vec<spi::Transaction> {
+    let get_client_state_tcp_command = 0x2f;
+
+    let mut expectations = mock_command(get_client_state_tcp_command, 0x1);
+
+    expectations.append(&mut mock_single_byte_size_params(1, 0x0)); // Send fake Socket
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(2));
+
+    expectations.append(&mut mock_receive(
+        get_client_state_tcp_command,
+        0x1,
+        &[state],
+    ));
+
+    expectations
+}
+
+pub fn mock_send_data_tcp(values: &[u8], written: u16) -> Vec<spi::Transaction> {
+    let mut expectations = mock_send_data_tcp_unconfirmed(values, written);
+
+    if written > 0 {
+        expectations.append(&mut mock_data_sent_tcp(0x1));
+    }
+
+    expectations
+}
+
+// Like mock_send_data_tcp(), but leaves checking that the data was sent to the caller
+pub fn mock_send_data_tcp_unconfirmed(values: &[u8], written: u16) -> Vec<spi::Transaction> {
+    let send_data_tcp_command = 0x44;
+
+    let mut expectations = mock_command(send_data_tcp_command, 0x2);
+
+    expectations.append(&mut mock_double_byte_size_params(&[0x0])); // Send fake Socket
+    expectations.append(&mut mock_double_byte_size_params(values));
+
+    expectations.append(&mut mock_end_byte());
+
+    let command_size = 4 + 4 + 1 + values.len();
+    expectations.append(&mut mock_padding(((4 - command_size % 4) % 4) as u8));
+
+    expectations.append(&mut mock_receive(
+        send_data_tcp_command,
+        0x1,
+        &written.to_le_bytes(),
+    ));
+
+    expectations
+}
+
+pub fn mock_data_sent_tcp(sent: u8) -> Vec<spi::Transaction> {
+    let data_sent_tcp_command = 0x2a;
+
+    let mut expectations = mock_command(data_sent_tcp_command, 0x1);
+
+    expectations.append(&mut mock_single_byte_size_params(1, 0x0)); // Send fake Socket
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(2));
+
+    expectations.append(&mut mock_receive(data_sent_tcp_command, 0x1, &[sent]));
+
+    expectations
 }
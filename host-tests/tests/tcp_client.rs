@@ -1,9 +1,15 @@
 use embedded_hal_mock::delay::MockNoop;
 use embedded_hal_mock::spi;
 
-use esp32_wroom_rp::network::{Hostname, IpAddress, Port, TransportMode};
-use esp32_wroom_rp::tcp_client::{Connect, TcpClient};
+use embedded_io::{Read, Write};
+
+use esp32_wroom_rp::gpio::TimeoutEspControl;
+use esp32_wroom_rp::network::{
+    ConnectionState, Hostname, IpAddress, NetworkError, Port, TransportMode,
+};
+use esp32_wroom_rp::tcp_client::{Connect, TcpClient, RECEIVE_POLL_INTERVAL_MS};
 use esp32_wroom_rp::wifi::Wifi;
+use esp32_wroom_rp::{BusError, Error, HalError};
 
 pub mod support;
 
@@ -148,9 +154,13 @@ fn successful_tcp_connection_with_hostname_invokes_closure() {
     let test_value = &mut value;
 
     TcpClient::build(&mut wifi)
-        .connect(hostname, port, mode, &mut delay, &mut |_tcp_client| {
-            *test_value = 2
-        })
+        .connect(
+            hostname,
+            port,
+            mode,
+            &mut delay,
+            &mut |_tcp_client, _delay| *test_value = 2,
+        )
         .unwrap();
 
     assert_eq!(value, 2);
@@ -255,9 +265,13 @@ fn successful_tcp_connection_with_ip_address_invokes_closure() {
     let test_value = &mut value;
 
     TcpClient::build(&mut wifi)
-        .connect(ip_address, port, mode, &mut delay, &mut |_tcp_client| {
-            *test_value = 2
-        })
+        .connect(
+            ip_address,
+            port,
+            mode,
+            &mut delay,
+            &mut |_tcp_client, _delay| *test_value = 2,
+        )
         .unwrap();
 
     assert_eq!(value, 2);
@@ -331,8 +345,7 @@ fn tcp_connection_timeout_error() {
             vec![0xff],
             vec![number_of_params_to_receive],
         ));
-        // test relies on max number of parameters being 8. This will probably change
-        // as we understand more.
+        // read 1-byte param length
         expectations.push(spi::Transaction::transfer(vec![0xff], vec![0x8]));
         // read full 8 byte buffer
         // The first byte is the connection state. We only consider a 0x4 to be a successful state
@@ -383,7 +396,7 @@ fn tcp_connection_timeout_error() {
         port,
         mode,
         &mut delay,
-        &mut |_tcp_client| {},
+        &mut |_tcp_client, _delay| {},
     );
 
     assert_eq!(
@@ -391,3 +404,785 @@ fn tcp_connection_timeout_error() {
         esp32_wroom_rp::Error::Network(esp32_wroom_rp::network::NetworkError::ConnectionTimeout)
     );
 }
+
+#[test]
+fn failed_spi_transfer_during_tcp_connect_returns_bus_error() {
+    let spi = FailingSpiMock {};
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tcp;
+
+    let result = TcpClient::build(&mut wifi).connect(
+        ip_address,
+        port,
+        mode,
+        &mut delay,
+        &mut |_tcp_client, _delay| {},
+    );
+
+    assert_eq!(
+        result.unwrap_err(),
+        Error::Bus(BusError::Transfer(HalError::new(&())))
+    );
+}
+
+#[test]
+fn failed_control_pin_during_tcp_connect_returns_bus_error() {
+    let spi = spi::Mock::new(&[]);
+
+    let mut delay = MockNoop::new();
+
+    let pins = FailingEspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tcp;
+
+    let result = TcpClient::build(&mut wifi).connect(
+        ip_address,
+        port,
+        mode,
+        &mut delay,
+        &mut |_tcp_client, _delay| {},
+    );
+
+    assert_eq!(
+        result.unwrap_err(),
+        Error::Bus(BusError::ControlPin(HalError::new(&"ack pin unreadable")))
+    );
+
+    wifi.destroy().done();
+}
+
+fn mock_connect_with_ip_address() -> Vec<spi::Transaction> {
+    let mut expectations = mock_get_socket(0x0);
+
+    // ----- start_client_tcp -----
+
+    let start_client_tcp_command = 0x2d;
+
+    expectations.append(&mut mock_command(start_client_tcp_command, 0x4));
+    expectations.append(&mut mock_single_byte_size_params(4, 0x40)); // Send fake IP Address
+    expectations.append(&mut mock_single_byte_size_params(2, 0x11)); // Send fake Port
+    expectations.append(&mut mock_single_byte_size_params(1, 0x0)); // Send fake Socket
+    expectations.append(&mut mock_single_byte_size_params(1, 0x0)); // Send fake Transport Mode
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_receive(start_client_tcp_command, 0x1, &[0x1]));
+
+    // ----- get_client_state_tcp -----
+
+    expectations.append(&mut mock_get_client_state_tcp(0x4)); // ConnectionState::Established
+
+    expectations
+}
+
+#[test]
+fn tcp_receive_reads_only_the_available_bytes() {
+    let mut expectations = mock_connect_with_ip_address();
+
+    expectations.append(&mut mock_avail_data_tcp(0x0, 5));
+    expectations.append(&mut mock_get_databuf_tcp(0x0, 5, b"hello"));
+    expectations.append(&mut mock_avail_data_tcp(0x0, 0));
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tcp;
+
+    let mut buffer = [0u8; 16];
+    let mut result = None;
+
+    TcpClient::build(&mut wifi)
+        .connect(
+            ip_address,
+            port,
+            mode,
+            &mut delay,
+            &mut |tcp_client, _delay| result = Some(tcp_client.receive(&mut buffer)),
+        )
+        .unwrap();
+
+    assert_eq!(result.unwrap().unwrap(), 5);
+    assert_eq!(&buffer[..5], b"hello");
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_receive_into_buffer_larger_than_one_nina_response() {
+    let first_chunk = [0x41; 1024];
+    let second_chunk = [0x42; 476];
+
+    let mut expectations = mock_connect_with_ip_address();
+
+    expectations.append(&mut mock_avail_data_tcp(0x0, 1500));
+    expectations.append(&mut mock_get_databuf_tcp(0x0, 1024, &first_chunk));
+    expectations.append(&mut mock_avail_data_tcp(0x0, 476));
+    expectations.append(&mut mock_get_databuf_tcp(0x0, 476, &second_chunk));
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tcp;
+
+    let mut buffer = [0u8; 1500];
+    let mut result = None;
+
+    TcpClient::build(&mut wifi)
+        .connect(
+            ip_address,
+            port,
+            mode,
+            &mut delay,
+            &mut |tcp_client, _delay| result = Some(tcp_client.receive(&mut buffer)),
+        )
+        .unwrap();
+
+    assert_eq!(result.unwrap().unwrap(), 1500);
+    assert_eq!(&buffer[..1024], &first_chunk[..]);
+    assert_eq!(&buffer[1024..], &second_chunk[..]);
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_peek_returns_next_byte_when_data_is_available() {
+    let mut expectations = mock_connect_with_ip_address();
+
+    expectations.append(&mut mock_avail_data_tcp(0x0, 3));
+
+    // ----- get_data_tcp -----
+
+    let get_data_tcp_command = 0x2c;
+
+    expectations.append(&mut mock_command(get_data_tcp_command, 0x2));
+
+    expectations.append(&mut mock_single_byte_size_params(1, 0x0)); // Send fake Socket
+    expectations.append(&mut mock_single_byte_size_params(2, 0x1)); // Send peek flag
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(3));
+
+    expectations.append(&mut mock_receive(get_data_tcp_command, 0x1, &[0x41]));
+
+    expectations.append(&mut mock_avail_data_tcp(0x0, 0));
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tcp;
+
+    let mut peeked = None;
+    let mut peeked_without_data = None;
+
+    TcpClient::build(&mut wifi)
+        .connect(
+            ip_address,
+            port,
+            mode,
+            &mut delay,
+            &mut |tcp_client, _delay| {
+                peeked = Some(tcp_client.peek());
+                peeked_without_data = Some(tcp_client.peek());
+            },
+        )
+        .unwrap();
+
+    assert_eq!(peeked.unwrap().unwrap(), Some(0x41));
+    assert_eq!(peeked_without_data.unwrap().unwrap(), None);
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_connection_stays_open_until_closed() {
+    let mut expectations = mock_connect_with_ip_address();
+
+    expectations.append(&mut mock_avail_data_tcp(0x0, 5));
+    expectations.append(&mut mock_get_databuf_tcp(0x0, 5, b"hello"));
+    expectations.append(&mut mock_avail_data_tcp(0x0, 0));
+    expectations.append(&mut mock_get_client_state_tcp(0x4)); // ConnectionState::Established
+    expectations.append(&mut mock_avail_data_tcp(0x0, 5));
+    expectations.append(&mut mock_get_databuf_tcp(0x0, 5, b"world"));
+    expectations.append(&mut mock_avail_data_tcp(0x0, 0));
+    expectations.append(&mut mock_get_client_state_tcp(0x7)); // ConnectionState::CloseWait
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tcp;
+
+    let mut connection = TcpClient::build(&mut wifi)
+        .open(ip_address, port, mode, &mut delay)
+        .unwrap();
+
+    let mut buffer = [0u8; 16];
+
+    assert_eq!(connection.receive(&mut buffer).unwrap(), 5);
+    assert_eq!(&buffer[..5], b"hello");
+    assert_eq!(connection.state().unwrap(), ConnectionState::Established);
+
+    assert_eq!(connection.receive(&mut buffer).unwrap(), 5);
+    assert_eq!(&buffer[..5], b"world");
+    assert_eq!(connection.state().unwrap(), ConnectionState::CloseWait);
+
+    connection.close().unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_connection_is_closed_when_dropped() {
+    let mut expectations = mock_connect_with_ip_address();
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tcp;
+
+    {
+        let connection = TcpClient::build(&mut wifi)
+            .open(ip_address, port, mode, &mut delay)
+            .unwrap();
+
+        assert_eq!(connection.server_ip_address(), Some(ip_address));
+        assert_eq!(connection.port(), port);
+    }
+
+    wifi.destroy().done();
+}
+
+fn mock_start_client_tcp_with_hostname(mode: u8, result: u8) -> Vec<spi::Transaction> {
+    let start_client_tcp_command = 0x2d;
+
+    let mut expectations = mock_command(start_client_tcp_command, 0x5);
+
+    expectations.append(&mut mock_single_byte_size_params(4, 0x46)); // Send hostname "FFFF"
+    expectations.append(&mut mock_single_byte_size_params(4, 0x0)); // Send empty IP Address
+    expectations.append(&mut mock_single_byte_size_params(2, 0x11)); // Send fake Port
+    expectations.append(&mut mock_single_byte_size_params(1, 0x0)); // Send fake Socket
+    expectations.append(&mut mock_single_byte_size_params(1, mode)); // Send Transport Mode
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(3));
+
+    expectations.append(&mut mock_receive(start_client_tcp_command, 0x1, &[result]));
+
+    expectations
+}
+
+#[test]
+fn tls_connection_with_hostname_sends_hostname_to_start_client_tcp() {
+    let mut expectations = mock_get_socket(0x0);
+
+    // No DNS lookup happens, the ESP32 target resolves the hostname itself
+    expectations.append(&mut mock_start_client_tcp_with_hostname(0x2, 0x1));
+    expectations.append(&mut mock_get_client_state_tcp(0x4)); // ConnectionState::Established
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let hostname: Hostname = "FFFF";
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tls;
+
+    let connection = TcpClient::build(&mut wifi)
+        .open(hostname, port, mode, &mut delay)
+        .unwrap();
+
+    assert_eq!(connection.server_hostname(), "FFFF");
+    assert_eq!(connection.mode(), TransportMode::Tls);
+
+    connection.close().unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tls_bearssl_connection_without_private_key_returns_client_credentials_not_set_error() {
+    let certificate = [0x43; 1100];
+
+    let mut expectations = mock_set_tls_credential(0x40, &certificate);
+
+    expectations.append(&mut mock_get_socket(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    wifi.set_client_certificate(&certificate).unwrap();
+
+    let hostname: Hostname = "FFFF";
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::TlsBearSsl;
+
+    let result = TcpClient::build(&mut wifi).open(hostname, port, mode, &mut delay);
+
+    assert_eq!(
+        result.err().unwrap(),
+        Error::Network(NetworkError::ClientCredentialsNotSet)
+    );
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn failed_tls_handshake_returns_tls_handshake_failed_error() {
+    let mut expectations = mock_get_socket(0x0);
+
+    expectations.append(&mut mock_start_client_tcp_with_hostname(0x2, 0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let hostname: Hostname = "FFFF";
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tls;
+
+    let result = TcpClient::build(&mut wifi).open(hostname, port, mode, &mut delay);
+
+    assert_eq!(
+        result.err().unwrap(),
+        Error::Network(NetworkError::TlsHandshakeFailed)
+    );
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn mutual_tls_connection_presents_client_credentials() {
+    let certificate = [0x43; 1100];
+    let private_key = [0x4b; 1600];
+
+    let mut expectations = mock_set_tls_credential(0x40, &certificate);
+
+    expectations.append(&mut mock_set_tls_credential(0x41, &private_key));
+    expectations.append(&mut mock_get_socket(0x0));
+    expectations.append(&mut mock_start_client_tcp_with_hostname(0x4, 0x1));
+    expectations.append(&mut mock_get_client_state_tcp(0x4)); // ConnectionState::Established
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    wifi.set_client_certificate(&certificate).unwrap();
+    wifi.set_private_key(&private_key).unwrap();
+
+    let hostname: Hostname = "FFFF";
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::TlsBearSsl;
+
+    let connection = TcpClient::build(&mut wifi)
+        .open(hostname, port, mode, &mut delay)
+        .unwrap();
+
+    connection.close().unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_connection_write_returns_bytes_written_by_esp32() {
+    let mut expectations = mock_connect_with_ip_address();
+
+    expectations.append(&mut mock_send_data_tcp(b"hello", 3));
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tcp;
+
+    let mut connection = TcpClient::build(&mut wifi)
+        .open(ip_address, port, mode, &mut delay)
+        .unwrap();
+
+    assert_eq!(connection.write(b"hello").unwrap(), 3);
+    connection.flush().unwrap();
+
+    connection.close().unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_connection_read_waits_for_data_and_returns_zero_once_closed() {
+    let mut expectations = mock_connect_with_ip_address();
+
+    expectations.append(&mut mock_avail_data_tcp(0x0, 0));
+    expectations.append(&mut mock_get_client_state_tcp(0x4)); // ConnectionState::Established
+    expectations.append(&mut mock_avail_data_tcp(0x0, 5));
+    expectations.append(&mut mock_get_databuf_tcp(0x0, 5, b"hello"));
+    expectations.append(&mut mock_avail_data_tcp(0x0, 0));
+    expectations.append(&mut mock_get_client_state_tcp(0x0)); // ConnectionState::Closed
+    expectations.append(&mut mock_avail_data_tcp(0x0, 0)); // Nothing arrived before closing
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tcp;
+
+    let mut connection = TcpClient::build(&mut wifi)
+        .open(ip_address, port, mode, &mut delay)
+        .unwrap();
+
+    let mut buffer = [0u8; 16];
+
+    assert_eq!(connection.read(&mut buffer).unwrap(), 5);
+    assert_eq!(&buffer[..5], b"hello");
+    assert_eq!(connection.read(&mut buffer).unwrap(), 0);
+
+    connection.close().unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_connection_read_waits_between_polls_and_retries_an_empty_read() {
+    let mut expectations = mock_connect_with_ip_address();
+
+    expectations.append(&mut mock_avail_data_tcp(0x0, 0));
+    expectations.append(&mut mock_get_client_state_tcp(0x4)); // ConnectionState::Established
+    expectations.append(&mut mock_avail_data_tcp(0x0, 5));
+    expectations.append(&mut mock_get_databuf_tcp(0x0, 5, b""));
+    expectations.append(&mut mock_avail_data_tcp(0x0, 5));
+    expectations.append(&mut mock_get_databuf_tcp(0x0, 5, b"hello"));
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let delay_counter = DelayCounter::default();
+    let pins = TimeoutEspControl::new(EspControlMock {}, delay_counter.clone());
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tcp;
+
+    let mut connection = TcpClient::build(&mut wifi)
+        .open(ip_address, port, mode, &mut delay)
+        .unwrap();
+
+    let mut buffer = [0u8; 16];
+
+    assert_eq!(connection.read(&mut buffer).unwrap(), 5);
+    assert_eq!(&buffer[..5], b"hello");
+    // Once after finding no data and once after the empty read
+    assert_eq!(
+        delay_counter.0.get(),
+        2 * RECEIVE_POLL_INTERVAL_MS as u32 * 1_000
+    );
+
+    connection.close().unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_connection_read_returns_data_that_arrived_before_the_connection_closed() {
+    let mut expectations = mock_connect_with_ip_address();
+
+    expectations.append(&mut mock_avail_data_tcp(0x0, 0));
+    expectations.append(&mut mock_get_client_state_tcp(0x7)); // ConnectionState::CloseWait
+    expectations.append(&mut mock_avail_data_tcp(0x0, 5));
+    expectations.append(&mut mock_avail_data_tcp(0x0, 5));
+    expectations.append(&mut mock_get_databuf_tcp(0x0, 5, b"hello"));
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tcp;
+
+    let mut connection = TcpClient::build(&mut wifi)
+        .open(ip_address, port, mode, &mut delay)
+        .unwrap();
+
+    let mut buffer = [0u8; 16];
+
+    assert_eq!(connection.read(&mut buffer).unwrap(), 5);
+    assert_eq!(&buffer[..5], b"hello");
+
+    connection.close().unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_send_returns_bytes_acknowledged_by_esp32() {
+    let mut expectations = mock_connect_with_ip_address();
+
+    let payload = [0x00, 0xff, 0x10, 0x80, 0x7f];
+    expectations.append(&mut mock_send_data_tcp(&payload, 3));
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tcp;
+
+    let mut connection = TcpClient::build(&mut wifi)
+        .open(ip_address, port, mode, &mut delay)
+        .unwrap();
+
+    assert_eq!(connection.send(&payload, &mut delay).unwrap(), 3);
+
+    connection.close().unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_send_waits_between_checks_until_esp32_confirms_data_was_sent() {
+    let mut expectations = mock_connect_with_ip_address();
+
+    let payload = b"hello";
+    expectations.append(&mut mock_send_data_tcp_unconfirmed(payload, 5));
+    expectations.append(&mut mock_data_sent_tcp(0x0));
+    expectations.append(&mut mock_data_sent_tcp(0x0));
+    expectations.append(&mut mock_data_sent_tcp(0x1));
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    // Plain control pins have no time source, so the delay passed in has to pace the checks
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tcp;
+
+    let mut connection = TcpClient::build(&mut wifi)
+        .open(ip_address, port, mode, &mut delay)
+        .unwrap();
+
+    let mut delay_counter = DelayCounter::default();
+
+    assert_eq!(connection.send(payload, &mut delay_counter).unwrap(), 5);
+    // 100 ms before each check after the first
+    assert_eq!(delay_counter.0.get(), 2 * 100_000);
+
+    connection.close().unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_connection_write_waits_between_checks_using_control_pins_delay() {
+    let mut expectations = mock_connect_with_ip_address();
+
+    let payload = b"hello";
+    expectations.append(&mut mock_send_data_tcp_unconfirmed(payload, 5));
+    expectations.append(&mut mock_data_sent_tcp(0x0));
+    expectations.append(&mut mock_data_sent_tcp(0x1));
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let delay_counter = DelayCounter::default();
+    let pins = TimeoutEspControl::new(EspControlMock {}, delay_counter.clone());
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tcp;
+
+    let mut connection = TcpClient::build(&mut wifi)
+        .open(ip_address, port, mode, &mut delay)
+        .unwrap();
+
+    assert_eq!(connection.write(payload).unwrap(), 5);
+    assert_eq!(delay_counter.0.get(), 100_000);
+
+    connection.close().unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_send_all_splits_large_payload_into_frames() {
+    let mut expectations = mock_connect_with_ip_address();
+
+    let payload: Vec<u8> = (0..1500).map(|i| i as u8).collect();
+    expectations.append(&mut mock_send_data_tcp(&payload[..1024], 1024));
+    expectations.append(&mut mock_send_data_tcp(&payload[1024..], 476));
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tcp;
+
+    let mut connection = TcpClient::build(&mut wifi)
+        .open(ip_address, port, mode, &mut delay)
+        .unwrap();
+
+    connection.send_all(&payload, &mut delay).unwrap();
+
+    connection.close().unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_send_all_resends_bytes_not_written_by_esp32() {
+    let mut expectations = mock_connect_with_ip_address();
+
+    expectations.append(&mut mock_send_data_tcp(b"hello", 3));
+    expectations.append(&mut mock_send_data_tcp(b"lo", 0));
+    expectations.append(&mut mock_send_data_tcp(b"lo", 2));
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ip_address: IpAddress = [0x40, 0x40, 0x40, 0x40];
+    let port: Port = 0x1111;
+    let mode: TransportMode = TransportMode::Tcp;
+
+    let mut connection = TcpClient::build(&mut wifi)
+        .open(ip_address, port, mode, &mut delay)
+        .unwrap();
+
+    connection.send_all(b"hello", &mut delay).unwrap();
+
+    connection.close().unwrap();
+
+    wifi.destroy().done();
+}
@@ -0,0 +1,165 @@
+use embedded_hal_mock::delay::MockNoop;
+use embedded_hal_mock::spi;
+
+use esp32_wroom_rp::network::ConnectionState;
+use esp32_wroom_rp::tcp_server::TcpServer;
+use esp32_wroom_rp::wifi::Wifi;
+
+pub mod support;
+
+use support::*;
+
+fn mock_start_server_tcp() -> Vec<spi::Transaction> {
+    let start_server_tcp_command = 0x28;
+
+    let mut expectations = mock_command(start_server_tcp_command, 0x3);
+
+    expectations.append(&mut mock_single_byte_size_params(2, 0x50)); // Send fake Port
+    expectations.append(&mut mock_single_byte_size_params(1, 0x0)); // Send fake Socket
+    expectations.append(&mut mock_single_byte_size_params(1, 0x0)); // Send TCP Transport Mode
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(1));
+
+    expectations.append(&mut mock_receive(start_server_tcp_command, 0x1, &[0x1]));
+
+    expectations
+}
+
+fn mock_get_state_tcp(state: u8) -> Vec<spi::Transaction> {
+    let get_state_tcp_command = 0x29;
+
+    let mut expectations = mock_command(get_state_tcp_command, 0x1);
+
+    expectations.append(&mut mock_single_byte_size_params(1, 0x0)); // Send fake Socket
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(2));
+
+    expectations.append(&mut mock_receive(get_state_tcp_command, 0x1, &[state]));
+
+    expectations
+}
+
+fn mock_get_remote_data(socket: u8) -> Vec<spi::Transaction> {
+    let get_remote_data_command = 0x3a;
+
+    let mut expectations = mock_command(get_remote_data_command, 0x1);
+
+    expectations.append(&mut mock_single_byte_size_params(1, socket));
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(2));
+
+    expectations.append(&mut mock_receive_params(
+        get_remote_data_command,
+        &[&[192, 168, 1, 20], &[0xc0, 0x01]],
+    ));
+
+    expectations
+}
+
+#[test]
+fn tcp_server_listens_on_port() {
+    let mut expectations = mock_get_socket(0x0);
+
+    expectations.append(&mut mock_start_server_tcp());
+    expectations.append(&mut mock_get_state_tcp(0x1)); // ConnectionState::Listening
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let mut tcp_server = TcpServer::build(&mut wifi);
+    tcp_server.listen(0x5050).unwrap();
+
+    assert_eq!(tcp_server.port(), 0x5050);
+    assert_eq!(tcp_server.state().unwrap(), ConnectionState::Listening);
+
+    tcp_server.close().unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_server_accept_with_no_client_waiting_returns_none() {
+    let mut expectations = mock_get_socket(0x0);
+
+    expectations.append(&mut mock_start_server_tcp());
+    expectations.append(&mut mock_avail_data_tcp(0x0, 255)); // No socket available
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let mut tcp_server = TcpServer::build(&mut wifi);
+    tcp_server.listen(0x5050).unwrap();
+
+    assert!(tcp_server.accept().unwrap().is_none());
+
+    tcp_server.close().unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn tcp_server_accept_yields_connection_on_client_socket() {
+    let client_socket = 0x1;
+
+    let mut expectations = mock_get_socket(0x0);
+
+    expectations.append(&mut mock_start_server_tcp());
+    expectations.append(&mut mock_avail_data_tcp(0x0, client_socket as u16));
+    expectations.append(&mut mock_get_remote_data(client_socket));
+    expectations.append(&mut mock_avail_data_tcp(client_socket, 3));
+    expectations.append(&mut mock_avail_data_tcp(client_socket, 3));
+    expectations.append(&mut mock_get_databuf_tcp(client_socket, 3, b"GET"));
+    expectations.append(&mut mock_avail_data_tcp(client_socket, 0));
+
+    // dropping the connection stops the client socket before the server socket is stopped
+    expectations.append(&mut mock_stop_client_tcp(client_socket));
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let mut tcp_server = TcpServer::build(&mut wifi);
+    tcp_server.listen(0x5050).unwrap();
+
+    {
+        let mut connection = tcp_server.accept().unwrap().unwrap();
+
+        assert_eq!(connection.server_ip_address(), Some([192, 168, 1, 20]));
+        assert_eq!(connection.port(), 0xc001);
+        assert_eq!(connection.available().unwrap(), 3);
+
+        let mut buffer = [0u8; 16];
+
+        assert_eq!(connection.receive(&mut buffer).unwrap(), 3);
+        assert_eq!(&buffer[..3], b"GET");
+    }
+
+    tcp_server.close().unwrap();
+
+    wifi.destroy().done();
+}
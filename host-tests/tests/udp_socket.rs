@@ -0,0 +1,227 @@
+use embedded_hal_mock::delay::MockNoop;
+use embedded_hal_mock::spi;
+
+use esp32_wroom_rp::network::{IpAddress, Port};
+use esp32_wroom_rp::udp_socket::UdpSocket;
+use esp32_wroom_rp::wifi::Wifi;
+
+pub mod support;
+
+use support::*;
+
+fn mock_start_server_udp() -> Vec<spi::Transaction> {
+    let start_server_tcp_command = 0x28;
+
+    let mut expectations = mock_command(start_server_tcp_command, 0x3);
+
+    expectations.append(&mut mock_single_byte_size_params(2, 0x22)); // Send fake Port
+    expectations.append(&mut mock_single_byte_size_params(1, 0x0)); // Send fake Socket
+    expectations.append(&mut mock_single_byte_size_params(1, 0x1)); // Send UDP Transport Mode
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(1));
+
+    expectations.append(&mut mock_receive(start_server_tcp_command, 0x1, &[0x1]));
+
+    expectations
+}
+
+fn mock_start_client_udp() -> Vec<spi::Transaction> {
+    let start_client_tcp_command = 0x2d;
+
+    let mut expectations = mock_command(start_client_tcp_command, 0x4);
+
+    expectations.append(&mut mock_single_byte_size_params(4, 0x40)); // Send fake IP Address
+    expectations.append(&mut mock_single_byte_size_params(2, 0x11)); // Send fake Port
+    expectations.append(&mut mock_single_byte_size_params(1, 0x0)); // Send fake Socket
+    expectations.append(&mut mock_single_byte_size_params(1, 0x1)); // Send UDP Transport Mode
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_receive(start_client_tcp_command, 0x1, &[0x1]));
+
+    expectations
+}
+
+fn mock_insert_databuf(values: &[u8]) -> Vec<spi::Transaction> {
+    let insert_databuf_command = 0x46;
+
+    let mut expectations = mock_command(insert_databuf_command, 0x2);
+
+    expectations.append(&mut mock_double_byte_size_params(&[0x0])); // Send fake Socket
+    expectations.append(&mut mock_double_byte_size_params(values));
+
+    expectations.append(&mut mock_end_byte());
+
+    let command_size = 4 + 4 + 1 + values.len();
+    expectations.append(&mut mock_padding(((4 - command_size % 4) % 4) as u8));
+
+    expectations.append(&mut mock_receive(insert_databuf_command, 0x1, &[0x1]));
+
+    expectations
+}
+
+fn mock_send_data_udp() -> Vec<spi::Transaction> {
+    let send_data_udp_command = 0x39;
+
+    let mut expectations = mock_command(send_data_udp_command, 0x1);
+
+    expectations.append(&mut mock_single_byte_size_params(1, 0x0)); // Send fake Socket
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(2));
+
+    expectations.append(&mut mock_receive(send_data_udp_command, 0x1, &[0x1]));
+
+    expectations
+}
+
+fn mock_get_remote_data(ip: IpAddress, port: Port) -> Vec<spi::Transaction> {
+    let get_remote_data_command = 0x3a;
+
+    let mut expectations = mock_command(get_remote_data_command, 0x1);
+
+    expectations.append(&mut mock_single_byte_size_params(1, 0x0)); // Send fake Socket
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(2));
+
+    expectations.append(&mut mock_receive_params(
+        get_remote_data_command,
+        &[&ip, &port.to_be_bytes()],
+    ));
+
+    expectations
+}
+
+#[test]
+fn udp_send_to_sends_a_single_datagram() {
+    let mut expectations = mock_get_socket(0x0);
+
+    expectations.append(&mut mock_start_client_udp());
+    expectations.append(&mut mock_insert_databuf(b"ping"));
+    expectations.append(&mut mock_send_data_udp());
+
+    // dropping the socket releases it
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    {
+        let mut udp_socket = UdpSocket::build(&mut wifi);
+
+        udp_socket
+            .send_to([0x40, 0x40, 0x40, 0x40], 0x1111, b"ping")
+            .unwrap();
+    }
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn udp_send_to_splits_large_datagram_across_nina_buffers() {
+    let first_chunk = [0x41; 1024];
+    let second_chunk = [0x42; 476];
+
+    let mut expectations = mock_get_socket(0x0);
+
+    expectations.append(&mut mock_start_client_udp());
+    expectations.append(&mut mock_insert_databuf(&first_chunk));
+    expectations.append(&mut mock_insert_databuf(&second_chunk));
+    expectations.append(&mut mock_send_data_udp());
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let mut datagram = [0x41; 1500];
+    datagram[1024..].copy_from_slice(&second_chunk);
+
+    let mut udp_socket = UdpSocket::build(&mut wifi);
+
+    udp_socket
+        .send_to([0x40, 0x40, 0x40, 0x40], 0x1111, &datagram)
+        .unwrap();
+
+    udp_socket.close().unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn udp_recv_from_reports_sender_address_and_port() {
+    let mut expectations = mock_get_socket(0x0);
+
+    expectations.append(&mut mock_start_server_udp());
+    expectations.append(&mut mock_avail_data_tcp(0x0, 4));
+    expectations.append(&mut mock_get_databuf_tcp(0x0, 4, b"pong"));
+    expectations.append(&mut mock_get_remote_data([192, 168, 1, 10], 123));
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let mut udp_socket = UdpSocket::build(&mut wifi);
+    udp_socket.bind(0x2222).unwrap();
+
+    let mut buffer = [0u8; 48];
+    let result = udp_socket.recv_from(&mut buffer).unwrap();
+
+    assert_eq!(result, Some((4, [192, 168, 1, 10], 123)));
+    assert_eq!(&buffer[..4], b"pong");
+    assert_eq!(udp_socket.local_port(), Some(0x2222));
+
+    udp_socket.close().unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn udp_recv_from_with_no_datagram_available_returns_none() {
+    let mut expectations = mock_get_socket(0x0);
+
+    expectations.append(&mut mock_start_server_udp());
+    expectations.append(&mut mock_avail_data_tcp(0x0, 0));
+
+    expectations.append(&mut mock_stop_client_tcp(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let mut udp_socket = UdpSocket::build(&mut wifi);
+    udp_socket.bind(0x2222).unwrap();
+
+    let mut buffer = [0u8; 48];
+
+    assert_eq!(udp_socket.recv_from(&mut buffer).unwrap(), None);
+
+    udp_socket.close().unwrap();
+
+    wifi.destroy().done();
+}
@@ -0,0 +1,1083 @@
+use embedded_hal_mock::delay::MockNoop;
+use embedded_hal_mock::spi;
+
+use esp32_wroom_rp::gpio::TimeoutEspControl;
+use esp32_wroom_rp::network::{IpConfig, MacAddress, NetworkError};
+use esp32_wroom_rp::protocol::ProtocolError;
+use esp32_wroom_rp::wifi::{
+    DisconnectReason, EncryptionType, JoinCredentials, Rssi, ScanResult, Wifi,
+};
+use esp32_wroom_rp::{BusError, Error, HalError};
+
+pub mod support;
+
+use support::*;
+
+fn mock_get_idx_command(command: u8, index: u8, values_to_receive: &[u8]) -> Vec<spi::Transaction> {
+    let mut expectations = mock_command(command, 0x1);
+
+    expectations.append(&mut mock_single_byte_size_params(1, index));
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(2));
+
+    expectations.append(&mut mock_receive(command, 0x1, values_to_receive));
+
+    expectations
+}
+
+#[test]
+fn scan_returns_a_result_for_each_network_found() {
+    // ----- start_scan_networks -----
+
+    let start_scan_networks_command = 0x36;
+
+    let mut expectations = mock_command(start_scan_networks_command, 0x0);
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_receive(start_scan_networks_command, 0x1, &[0x1]));
+
+    // ----- scan_networks -----
+
+    let scan_networks_command = 0x27;
+
+    expectations.append(&mut mock_command(scan_networks_command, 0x0));
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_receive_params(
+        scan_networks_command,
+        &[b"home", b"office"],
+    ));
+
+    // ----- per network details -----
+
+    let networks: [(i32, u8, [u8; 6], u8); 2] = [
+        (-45, 0x4, [0x06, 0x05, 0x04, 0x03, 0x02, 0x01], 6),
+        (-80, 0x7, [0x16, 0x15, 0x14, 0x13, 0x12, 0x11], 11),
+    ];
+
+    for (index, (rssi, encryption, bssid, channel)) in networks.iter().enumerate() {
+        let index = index as u8;
+        expectations.append(&mut mock_get_idx_command(0x32, index, &rssi.to_le_bytes()));
+        expectations.append(&mut mock_get_idx_command(0x33, index, &[*encryption]));
+        expectations.append(&mut mock_get_idx_command(0x3c, index, bssid));
+        expectations.append(&mut mock_get_idx_command(0x3d, index, &[*channel]));
+    }
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let scan_results = wifi.scan().unwrap();
+
+    assert_eq!(
+        scan_results.as_slice(),
+        &[
+            ScanResult {
+                ssid: "home".into(),
+                rssi: Rssi(-45),
+                encryption: EncryptionType::Ccmp,
+                bssid: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+                channel: 6,
+            },
+            ScanResult {
+                ssid: "office".into(),
+                rssi: Rssi(-80),
+                encryption: EncryptionType::None,
+                bssid: [0x11, 0x12, 0x13, 0x14, 0x15, 0x16],
+                channel: 11,
+            },
+        ]
+    );
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn scan_with_no_networks_found_returns_no_results() {
+    let start_scan_networks_command = 0x36;
+
+    let mut expectations = mock_command(start_scan_networks_command, 0x0);
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_receive(start_scan_networks_command, 0x1, &[0x1]));
+
+    let scan_networks_command = 0x27;
+
+    expectations.append(&mut mock_command(scan_networks_command, 0x0));
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_receive_params(scan_networks_command, &[]));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert!(wifi.scan().unwrap().is_empty());
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn failed_scan_returns_scan_failed_error() {
+    let start_scan_networks_command = 0x36;
+
+    let mut expectations = mock_command(start_scan_networks_command, 0x0);
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_receive(start_scan_networks_command, 0x1, &[0x0]));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(
+        wifi.scan().unwrap_err(),
+        esp32_wroom_rp::Error::Network(esp32_wroom_rp::network::NetworkError::ScanFailed)
+    );
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn failed_spi_transfer_returns_bus_error() {
+    let spi = FailingSpiMock {};
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(
+        wifi.firmware_version().unwrap_err(),
+        Error::Bus(BusError::Transfer(HalError::new(&())))
+    );
+    assert_eq!(
+        wifi.join("ssid", "passphrase").unwrap_err(),
+        Error::Bus(BusError::Transfer(HalError::new(&())))
+    );
+}
+
+#[test]
+fn failed_control_pin_returns_bus_error() {
+    let spi = spi::Mock::new(&[]);
+
+    let mut delay = MockNoop::new();
+
+    let pins = FailingEspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(
+        wifi.get_connection_status().unwrap_err(),
+        Error::Bus(BusError::ControlPin(HalError::new(&"ack pin unreadable")))
+    );
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn unresponsive_esp32_target_returns_communication_timeout_error() {
+    let spi = spi::Mock::new(&[]);
+
+    let mut delay = MockNoop::new();
+
+    let pins = TimeoutEspControl::new(UnresponsiveEspControlMock {}, MockNoop::new());
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+    wifi.set_command_timeout(1);
+
+    assert_eq!(
+        wifi.firmware_version().unwrap_err(),
+        Error::Protocol(ProtocolError::CommunicationTimeout)
+    );
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn client_certificate_is_sent_whole_since_nina_firmware_replaces_it_on_every_command() {
+    let certificate = [0x43; 1200];
+
+    let expectations = mock_set_tls_credential(0x40, &certificate);
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    wifi.set_client_certificate(&certificate).unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn client_certificate_too_large_for_esp32_target_returns_payload_too_large_error() {
+    let spi = spi::Mock::new(&[]);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(
+        wifi.set_client_certificate(&[0x43; 1301]).unwrap_err(),
+        Error::Protocol(ProtocolError::PayloadTooLarge)
+    );
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn set_private_key_sends_key() {
+    let private_key = [0x4b; 1700];
+
+    let expectations = mock_set_tls_credential(0x41, &private_key);
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    wifi.set_private_key(&private_key).unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn private_key_too_large_for_esp32_target_returns_payload_too_large_error() {
+    let spi = spi::Mock::new(&[]);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(
+        wifi.set_private_key(&[0x4b; 1701]).unwrap_err(),
+        Error::Protocol(ProtocolError::PayloadTooLarge)
+    );
+
+    wifi.destroy().done();
+}
+
+fn mock_set_ap_net(result: u8) -> Vec<spi::Transaction> {
+    let set_ap_net_command = 0x18;
+
+    let mut expectations = mock_command(set_ap_net_command, 0x2);
+
+    expectations.append(&mut mock_single_byte_size_params(4, 0x46)); // SSID is "FFFF"
+    expectations.append(&mut mock_single_byte_size_params(1, 0x6)); // Channel
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(1));
+
+    expectations.append(&mut mock_receive(set_ap_net_command, 0x1, &[result]));
+
+    expectations
+}
+
+#[test]
+fn start_open_access_point_sends_ssid_and_channel() {
+    let mut expectations = mock_set_ap_net(0x1);
+
+    expectations.append(&mut mock_get_conn_status(0x7)); // ConnectionStatus::ApListening
+    expectations.append(&mut mock_get_conn_status(0x8)); // ConnectionStatus::ApConnected
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    wifi.start_access_point("FFFF", None, 6).unwrap();
+
+    assert!(!wifi.access_point_has_stations().unwrap());
+    assert!(wifi.access_point_has_stations().unwrap());
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn start_access_point_with_passphrase_sends_ssid_passphrase_and_channel() {
+    let set_ap_passphrase_command = 0x19;
+
+    let mut expectations = mock_command(set_ap_passphrase_command, 0x3);
+
+    expectations.append(&mut mock_single_byte_size_params(4, 0x46)); // SSID is "FFFF"
+    expectations.append(&mut mock_single_byte_size_params(8, 0x50)); // Passphrase is "PPPPPPPP"
+    expectations.append(&mut mock_single_byte_size_params(1, 0x6)); // Channel
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_receive(set_ap_passphrase_command, 0x1, &[0x1]));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    wifi.start_access_point("FFFF", Some("PPPPPPPP"), 6)
+        .unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn failed_access_point_start_returns_access_point_failed_error() {
+    let expectations = mock_set_ap_net(0x0);
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(
+        wifi.start_access_point("FFFF", None, 6).unwrap_err(),
+        Error::Network(NetworkError::AccessPointFailed)
+    );
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn start_access_point_with_invalid_ssid_or_passphrase_returns_invalid_credentials_error() {
+    let spi = spi::Mock::new(&[]);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(
+        wifi.start_access_point("", None, 6).unwrap_err(),
+        Error::Network(NetworkError::InvalidCredentials)
+    );
+    assert_eq!(
+        wifi.start_access_point(&"F".repeat(33), None, 6)
+            .unwrap_err(),
+        Error::Network(NetworkError::InvalidCredentials)
+    );
+    assert_eq!(
+        wifi.start_access_point("FFFF", Some("short"), 6)
+            .unwrap_err(),
+        Error::Network(NetworkError::InvalidCredentials)
+    );
+    assert_eq!(
+        wifi.start_access_point("FFFF", Some(&"P".repeat(64)), 6)
+            .unwrap_err(),
+        Error::Network(NetworkError::InvalidCredentials)
+    );
+
+    // Nothing was sent to the ESP32 target
+    wifi.destroy().done();
+}
+
+#[test]
+fn set_ip_config_sends_all_three_addresses_as_valid() {
+    let set_ip_config_command = 0x14;
+
+    let mut expectations = mock_command(set_ip_config_command, 0x4);
+
+    expectations.append(&mut mock_single_byte_size_params(1, 0x3)); // Number of valid params
+    expectations.append(&mut mock_single_byte_size_params(4, 0x0a)); // Local IP
+    expectations.append(&mut mock_single_byte_size_params(4, 0x01)); // Gateway
+    expectations.append(&mut mock_single_byte_size_params(4, 0xff)); // Subnet
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(3));
+
+    expectations.append(&mut mock_receive(set_ip_config_command, 0x1, &[0x1]));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    wifi.set_ip_config([10, 10, 10, 10], [1, 1, 1, 1], [255, 255, 255, 255])
+        .unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn ip_config_parses_ip_netmask_and_gateway() {
+    let get_ip_addr_command = 0x21;
+
+    let mut expectations = mock_command(get_ip_addr_command, 0x1);
+
+    expectations.append(&mut mock_single_byte_size_params(1, 0xff)); // Dummy param
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(2));
+
+    expectations.append(&mut mock_receive_params(
+        get_ip_addr_command,
+        &[&[192, 168, 1, 42], &[255, 255, 255, 0], &[192, 168, 1, 1]],
+    ));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(
+        wifi.ip_config().unwrap(),
+        IpConfig {
+            ip: [192, 168, 1, 42],
+            netmask: [255, 255, 255, 0],
+            gateway: [192, 168, 1, 1],
+        }
+    );
+
+    wifi.destroy().done();
+}
+
+fn mock_get_current_command(command: u8, values_to_receive: &[u8]) -> Vec<spi::Transaction> {
+    let mut expectations = mock_command(command, 0x1);
+
+    expectations.append(&mut mock_single_byte_size_params(1, 0xff)); // Dummy param
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_padding(2));
+
+    expectations.append(&mut mock_receive(command, 0x1, values_to_receive));
+
+    expectations
+}
+
+#[test]
+fn current_link_details_are_returned_as_typed_values() {
+    // MAC address and BSSID are sent least significant byte first
+    let mut expectations = mock_get_current_command(0x22, &[0x66, 0x55, 0x44, 0x33, 0x22, 0x11]);
+
+    expectations.append(&mut mock_get_current_command(0x23, b"home network"));
+    expectations.append(&mut mock_get_current_command(
+        0x24,
+        &[0x0f, 0x0e, 0x0d, 0x0c, 0x0b, 0x0a],
+    ));
+    expectations.append(&mut mock_get_current_command(0x25, &(-67i32).to_le_bytes()));
+    expectations.append(&mut mock_get_current_command(0x26, &[0x4]));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(
+        wifi.mac_address().unwrap(),
+        MacAddress([0x11, 0x22, 0x33, 0x44, 0x55, 0x66])
+    );
+    assert_eq!(wifi.current_ssid().unwrap(), "home network");
+    assert_eq!(
+        wifi.current_bssid().unwrap(),
+        MacAddress([0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f])
+    );
+    assert_eq!(wifi.rssi().unwrap(), Rssi(-67));
+    assert_eq!(wifi.encryption_type().unwrap(), EncryptionType::Ccmp);
+
+    wifi.destroy().done();
+}
+
+fn mock_set_string_param(command: u8, length: u8, byte_value: u8) -> Vec<spi::Transaction> {
+    let mut expectations = mock_command(command, 0x1);
+
+    expectations.append(&mut mock_single_byte_size_params(length, byte_value));
+
+    expectations.append(&mut mock_end_byte());
+
+    let command_size = 4 + 1 + length;
+    expectations.append(&mut mock_padding((4 - command_size % 4) % 4));
+
+    expectations.append(&mut mock_receive(command, 0x1, &[0x1]));
+
+    expectations
+}
+
+fn mock_set_ent_enable() -> Vec<spi::Transaction> {
+    let set_ent_enable_command = 0x4f;
+
+    let mut expectations = mock_command(set_ent_enable_command, 0x0);
+
+    expectations.append(&mut mock_end_byte());
+
+    expectations.append(&mut mock_receive(set_ent_enable_command, 0x1, &[0x1]));
+
+    expectations
+}
+
+#[test]
+fn join_enterprise_sends_credentials_before_enabling_and_setting_network() {
+    let mut expectations = mock_set_string_param(0x4a, 4, 0x61); // Identity is "aaaa"
+
+    expectations.append(&mut mock_set_string_param(0x4b, 4, 0x75)); // Username is "uuuu"
+    expectations.append(&mut mock_set_string_param(0x4c, 4, 0x70)); // Password is "pppp"
+    expectations.append(&mut mock_set_ent_enable());
+    expectations.append(&mut mock_set_string_param(0x10, 4, 0x46)); // SSID is "FFFF"
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    wifi.join_enterprise("FFFF", "aaaa", "uuuu", "pppp", None)
+        .unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn join_enterprise_with_ca_cert_sends_ca_cert() {
+    let ca_cert = [0x43; 300];
+
+    let mut expectations = mock_set_string_param(0x4a, 4, 0x61); // Identity is "aaaa"
+
+    expectations.append(&mut mock_set_string_param(0x4b, 4, 0x75)); // Username is "uuuu"
+    expectations.append(&mut mock_set_string_param(0x4c, 4, 0x70)); // Password is "pppp"
+    expectations.append(&mut mock_set_tls_credential(0x4d, &ca_cert));
+    expectations.append(&mut mock_set_ent_enable());
+    expectations.append(&mut mock_set_string_param(0x10, 4, 0x46)); // SSID is "FFFF"
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    wifi.join_enterprise("FFFF", "aaaa", "uuuu", "pppp", Some(&ca_cert))
+        .unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn join_enterprise_with_too_long_identity_returns_invalid_credentials_error() {
+    let spi = spi::Mock::new(&[]);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let identity = "a".repeat(33);
+
+    assert_eq!(
+        wifi.join_enterprise("FFFF", &identity, "uuuu", "pppp", None)
+            .unwrap_err(),
+        Error::Network(NetworkError::InvalidCredentials)
+    );
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn connect_to_open_network_sends_set_net() {
+    let expectations = mock_set_string_param(0x10, 4, 0x46); // SSID is "FFFF"
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    wifi.connect("FFFF", JoinCredentials::Open).unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn connect_to_wep_network_sends_set_key_with_key_index() {
+    let set_key_command = 0x12;
+
+    let mut expectations = mock_command(set_key_command, 0x3);
+
+    expectations.append(&mut mock_single_byte_size_params(4, 0x46)); // SSID is "FFFF"
+    expectations.append(&mut mock_single_byte_size_params(1, 0x1)); // Key index is 1
+    expectations.append(&mut mock_single_byte_size_params(5, 0x6b)); // Key is "kkkkk"
+    expectations.append(&mut mock_end_byte());
+    expectations.append(&mut mock_padding(3));
+    expectations.append(&mut mock_receive(set_key_command, 0x1, &[0x1]));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    wifi.connect(
+        "FFFF",
+        JoinCredentials::Wep {
+            index: 1,
+            key: "kkkkk",
+        },
+    )
+    .unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn connect_with_invalid_credentials_returns_invalid_credentials_error() {
+    let spi = spi::Mock::new(&[]);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let ssid = "F".repeat(33);
+
+    assert_eq!(
+        wifi.connect(&ssid, JoinCredentials::Open).unwrap_err(),
+        Error::Network(NetworkError::InvalidCredentials)
+    );
+    assert_eq!(
+        wifi.connect("FFFF", JoinCredentials::Wpa("short"))
+            .unwrap_err(),
+        Error::Network(NetworkError::InvalidCredentials)
+    );
+    // A 64 character passphrase is a pre-shared key, so it must be all hex digits
+    assert_eq!(
+        wifi.connect("FFFF", JoinCredentials::Wpa(&"g".repeat(64)))
+            .unwrap_err(),
+        Error::Network(NetworkError::InvalidCredentials)
+    );
+    assert_eq!(
+        wifi.connect(
+            "FFFF",
+            JoinCredentials::Wep {
+                index: 4,
+                key: "kkkkk"
+            }
+        )
+        .unwrap_err(),
+        Error::Network(NetworkError::InvalidCredentials)
+    );
+    assert_eq!(
+        wifi.connect(
+            "FFFF",
+            JoinCredentials::Wep {
+                index: 0,
+                key: "kkkkkk"
+            }
+        )
+        .unwrap_err(),
+        Error::Network(NetworkError::InvalidCredentials)
+    );
+
+    wifi.destroy().done();
+}
+
+fn mock_set_passphrase() -> Vec<spi::Transaction> {
+    let set_passphrase_command = 0x11;
+
+    let mut expectations = mock_command(set_passphrase_command, 0x2);
+
+    expectations.append(&mut mock_single_byte_size_params(4, 0x46)); // SSID is "FFFF"
+    expectations.append(&mut mock_single_byte_size_params(8, 0x70)); // Passphrase is "pppppppp"
+    expectations.append(&mut mock_end_byte());
+    expectations.append(&mut mock_padding(2));
+    expectations.append(&mut mock_receive(set_passphrase_command, 0x1, &[0x1]));
+
+    expectations
+}
+
+#[test]
+fn join_and_wait_returns_once_connected() {
+    let mut expectations = mock_set_passphrase();
+
+    expectations.append(&mut mock_get_conn_status(0x0)); // Idle
+    expectations.append(&mut mock_get_conn_status(0x3)); // Connected
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    wifi.join_and_wait("FFFF", "pppppppp", 10_000, &mut delay)
+        .unwrap();
+
+    wifi.destroy().done();
+}
+
+fn mock_get_reason_code(reason_code: u8) -> Vec<spi::Transaction> {
+    let get_reason_code_command = 0x1f;
+
+    let mut expectations = mock_command(get_reason_code_command, 0x0);
+    expectations.append(&mut mock_end_byte());
+    expectations.append(&mut mock_receive(
+        get_reason_code_command,
+        0x1,
+        &[reason_code],
+    ));
+
+    expectations
+}
+
+#[test]
+fn join_and_wait_failure_returns_disconnect_reason() {
+    let mut expectations = mock_set_passphrase();
+
+    expectations.append(&mut mock_get_conn_status(0x4)); // Failed
+    expectations.append(&mut mock_get_reason_code(202));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(
+        wifi.join_and_wait("FFFF", "pppppppp", 10_000, &mut delay)
+            .unwrap_err(),
+        Error::Network(NetworkError::JoinFailed(DisconnectReason::AuthFailed))
+    );
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn join_and_wait_with_wrong_passphrase_returns_four_way_handshake_timeout() {
+    let mut expectations = mock_set_passphrase();
+
+    // NINA firmware reports a wrong passphrase as Disconnected rather than Failed
+    expectations.append(&mut mock_get_conn_status(0x0)); // Idle
+    expectations.append(&mut mock_get_conn_status(0x6)); // Disconnected
+    expectations.append(&mut mock_get_reason_code(15));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(
+        wifi.join_and_wait("FFFF", "pppppppp", 10_000, &mut delay)
+            .unwrap_err(),
+        Error::Network(NetworkError::JoinFailed(
+            DisconnectReason::FourWayHandshakeTimeout
+        ))
+    );
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn join_and_wait_gives_up_after_timeout() {
+    let mut expectations = mock_set_passphrase();
+
+    expectations.append(&mut mock_get_conn_status(0x0)); // Idle
+    expectations.append(&mut mock_get_conn_status(0x0));
+    expectations.append(&mut mock_get_conn_status(0x0));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(
+        wifi.join_and_wait("FFFF", "pppppppp", 200, &mut delay)
+            .unwrap_err(),
+        Error::Network(NetworkError::JoinTimeout)
+    );
+
+    wifi.destroy().done();
+}
+
+fn mock_ping(round_trip_ms: i16) -> Vec<spi::Transaction> {
+    let ping_command = 0x3e;
+
+    let mut expectations = mock_command(ping_command, 0x2);
+
+    expectations.append(&mut mock_single_byte_size_params(4, 0x40)); // Send fake IP Address
+    expectations.append(&mut mock_single_byte_size_params(1, 0x80)); // TTL is 128
+    expectations.append(&mut mock_end_byte());
+    expectations.append(&mut mock_padding(1));
+    expectations.append(&mut mock_receive(
+        ping_command,
+        0x1,
+        &round_trip_ms.to_le_bytes(),
+    ));
+
+    expectations
+}
+
+#[test]
+fn ping_returns_round_trip_time() {
+    let expectations = mock_ping(12);
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(wifi.ping([0x40, 0x40, 0x40, 0x40], 128).unwrap(), 12);
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn ping_failure_returns_timeout_or_unreachable_error() {
+    let mut expectations = mock_ping(-2);
+
+    expectations.append(&mut mock_ping(-1));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(
+        wifi.ping([0x40, 0x40, 0x40, 0x40], 128).unwrap_err(),
+        Error::Network(NetworkError::PingTimeout)
+    );
+    assert_eq!(
+        wifi.ping([0x40, 0x40, 0x40, 0x40], 128).unwrap_err(),
+        Error::Network(NetworkError::HostUnreachable)
+    );
+
+    wifi.destroy().done();
+}
+
+fn mock_req_host_by_name(result: u8) -> Vec<spi::Transaction> {
+    let req_host_by_name_command = 0x34;
+
+    let mut expectations = mock_command(req_host_by_name_command, 0x1);
+
+    expectations.append(&mut mock_single_byte_size_params(4, 0x46)); // hostname is "FFFF"
+    expectations.append(&mut mock_end_byte());
+    expectations.append(&mut mock_padding(3));
+    expectations.append(&mut mock_receive(req_host_by_name_command, 0x1, &[result]));
+
+    expectations
+}
+
+fn mock_get_host_by_name(ip: [u8; 4]) -> Vec<spi::Transaction> {
+    let get_host_by_name_command = 0x35;
+
+    let mut expectations = mock_command(get_host_by_name_command, 0x0);
+
+    expectations.append(&mut mock_end_byte());
+    expectations.append(&mut mock_receive(get_host_by_name_command, 0x1, &ip));
+
+    expectations
+}
+
+#[test]
+fn ping_hostname_resolves_hostname_then_pings_its_ip_address() {
+    let mut expectations = mock_req_host_by_name(1);
+
+    expectations.append(&mut mock_get_host_by_name([0x40, 0x40, 0x40, 0x40]));
+    expectations.append(&mut mock_ping(12));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(wifi.ping_hostname("FFFF", 128).unwrap(), 12);
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn ping_hostname_returns_dns_resolve_failed_error_without_pinging() {
+    // The hostname lookup is rejected, then a later one resolves to no address
+    let mut expectations = mock_req_host_by_name(0);
+
+    expectations.append(&mut mock_req_host_by_name(1));
+    expectations.append(&mut mock_get_host_by_name([0xff, 0xff, 0xff, 0xff]));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(
+        wifi.ping_hostname("FFFF", 128).unwrap_err(),
+        Error::Network(NetworkError::DnsResolveFailed)
+    );
+    assert_eq!(
+        wifi.ping_hostname("FFFF", 128).unwrap_err(),
+        Error::Network(NetworkError::DnsResolveFailed)
+    );
+
+    wifi.destroy().done();
+}
+
+fn mock_get_time(seconds: u32) -> Vec<spi::Transaction> {
+    let get_time_command = 0x3b;
+
+    let mut expectations = mock_command(get_time_command, 0x0);
+
+    expectations.append(&mut mock_end_byte());
+    expectations.append(&mut mock_receive(
+        get_time_command,
+        0x1,
+        &seconds.to_le_bytes(),
+    ));
+
+    expectations
+}
+
+#[test]
+fn network_time_before_sync_returns_time_not_synced_error() {
+    let expectations = mock_get_time(0);
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(
+        wifi.network_time().unwrap_err(),
+        Error::Network(NetworkError::TimeNotSynced)
+    );
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn network_time_returns_unix_seconds_once_synced() {
+    let mut expectations = mock_get_time(0);
+
+    expectations.append(&mut mock_get_time(1_700_000_000));
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    assert_eq!(
+        wifi.network_time().unwrap_err(),
+        Error::Network(NetworkError::TimeNotSynced)
+    );
+    assert_eq!(wifi.network_time().unwrap(), 1_700_000_000);
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn set_hostname_sends_hostname() {
+    let expectations = mock_set_string_param(0x16, 8, 0x68); // Hostname is "hhhhhhhh"
+
+    let spi = spi::Mock::new(&expectations);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    wifi.set_hostname("hhhhhhhh").unwrap();
+
+    wifi.destroy().done();
+}
+
+#[test]
+fn set_hostname_with_too_long_hostname_returns_payload_too_large_error() {
+    let spi = spi::Mock::new(&[]);
+
+    let mut delay = MockNoop::new();
+
+    let pins = EspControlMock {};
+
+    let mut wifi = Wifi::init(spi, pins, &mut delay).ok().unwrap();
+
+    let hostname = "h".repeat(256);
+
+    assert_eq!(
+        wifi.set_hostname(&hostname).unwrap_err(),
+        Error::Protocol(ProtocolError::PayloadTooLarge)
+    );
+
+    wifi.destroy().done();
+}